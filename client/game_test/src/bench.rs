@@ -0,0 +1,72 @@
+/*
+Speed of the Connect 4 search: time to each depth and, when built with
+`--features node-count`, the nodes the search visited and nodes a second, for the
+single-threaded search and, when built with `--features parallel`, the threaded one
+*/
+use crate::connect4::Connect4;
+use crate::con4_ai;
+use std::time::{Duration, Instant};
+
+/// Positions searched at every depth: the empty board and a middlegame
+const POSITIONS: [&[usize]; 2] = [&[], &[3, 3, 2, 4, 4, 2, 5, 1]];
+
+/// How long `search` took and the positions the minimax counted while it ran,
+/// 0 without the `node-count` feature
+fn time(search: impl FnOnce()) -> (Duration, u64)
+{
+    #[cfg(feature = "node-count")]
+    con4_ai::take_nodes();
+
+    let start = Instant::now();
+    search();
+    let elapsed = start.elapsed();
+
+    #[cfg(feature = "node-count")]
+    return (elapsed, con4_ai::take_nodes());
+    #[cfg(not(feature = "node-count"))]
+    return (elapsed, 0);
+}
+
+fn nodes_per_second(nodes: u64, time: Duration) -> f64
+{
+    nodes as f64 / time.as_secs_f64().max(1e-9)
+}
+
+pub fn benchmark(max_depth: usize)
+{
+    let heuristic = con4_ai::classic();
+
+    for moves in POSITIONS.iter()
+    {
+        let mut board = Connect4::initialize();
+        for col in moves.iter()
+        {
+            board.place(*col);
+        }
+        println!("----------------------------------------------------");
+        println!("Position after moves {:?}", moves);
+
+        #[cfg(feature = "parallel")]
+        println!("{:>5} {:>12} {:>12} {:>14} {:>12} {:>14} {:>8}", "depth", "nodes", "serial", "nodes/s", "parallel", "nodes/s", "speedup");
+        #[cfg(not(feature = "parallel"))]
+        println!("{:>5} {:>12} {:>12} {:>14}", "depth", "nodes", "serial", "nodes/s");
+
+        for depth in 1..=max_depth
+        {
+            let (serial, nodes) = time(|| { con4_ai::score_moves(board, depth, heuristic); });
+            print!("{:>5} {:>12} {:>11.3}s {:>14.0}", depth, nodes, serial.as_secs_f64(), nodes_per_second(nodes, serial));
+
+            #[cfg(feature = "parallel")]
+            {
+                let (parallel, nodes) = time(|| { con4_ai::score_moves_parallel(board, depth, heuristic); });
+                print!(" {:>11.3}s {:>14.0} {:>7.2}x", parallel.as_secs_f64(), nodes_per_second(nodes, parallel), serial.as_secs_f64() / parallel.as_secs_f64().max(1e-9));
+            }
+            println!();
+        }
+    }
+
+    #[cfg(not(feature = "node-count"))]
+    println!("Build with `--features node-count` to count the nodes searched");
+    #[cfg(not(feature = "parallel"))]
+    println!("Build with `--features parallel` to compare the threaded search");
+}
@@ -0,0 +1,176 @@
+// The built-in AIs behind the engine protocol described in protocol.rs
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::io::{self, BufRead, Write};
+
+use game_core::connect4::{connect4::Connect4, con4_ai};
+use game_core::toot_and_otto::{toot_and_otto::TootAndOtto, toot_ai};
+use game_core::types::{analysis::{Analysis, Limit}, strength::Strength};
+use game_test::protocol;
+
+/// Thinking time when `go` names no limit
+const DEFAULT_MOVETIME: u64 = 1000;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Game
+{
+    Connect4,
+    Toot,
+}
+
+/// The limits a `go` command asked for
+struct Go
+{
+    limit: Limit,
+    level: Option<f64>,
+}
+
+fn parse_go(words: &[&str]) -> Option<Go>
+{
+    let mut go = Go { limit: Limit::Millis(DEFAULT_MOVETIME), level: None };
+
+    for pair in words.chunks(2)
+    {
+        match pair
+        {
+            ["movetime", millis] => go.limit = Limit::Millis(millis.parse().ok()?),
+            ["depth", depth] => go.limit = Limit::Depth(depth.parse().ok()?),
+            ["level", level] => go.level = Some(level.parse().ok()?),
+            _ => return None,
+        }
+    }
+
+    Some(go)
+}
+
+/// Prints the search result as an "info" line followed by "bestmove"
+fn report<M>(analysis: &Analysis<M>, notation: impl Fn(&M) -> String)
+{
+    let best = match analysis.moves.first()
+    {
+        Some(best) => best,
+        None => {
+            println!("error game is over");
+            return;
+        }
+    };
+
+    let mate = match best.mate
+    {
+        Some(plies) => format!(" mate {}", plies),
+        None => String::new(),
+    };
+    let pv: Vec<String> = best.pv.iter().map(&notation).collect();
+
+    println!("info depth {} score {}{} pv {}", analysis.depth, best.score, mate, pv.join(" "));
+    println!("bestmove {}", notation(&best.mv));
+}
+
+fn go_connect4(moves: &[String], go: &Go, rng: &mut StdRng)
+{
+    let mut board = Connect4::initialize();
+    for mv in moves.iter()
+    {
+        match protocol::parse_connect4(mv)
+        {
+            Some(col) if !board.termination && board.place(col) => {},
+            _ => {
+                println!("error illegal move {}", mv);
+                return;
+            }
+        }
+    }
+
+    if board.termination
+    {
+        println!("error game is over");
+    }
+    else if let Some(level) = go.level
+    {
+        match con4_ai::ai_next_move(board, Strength::from_level(level), con4_ai::classic(), rng)
+        {
+            Some(col) => println!("bestmove {}", protocol::connect4_notation(col)),
+            None => println!("error game is over"),
+        }
+    }
+    else
+    {
+        report(&con4_ai::analyze(board, go.limit, con4_ai::classic()), |col| protocol::connect4_notation(*col));
+    }
+}
+
+fn go_toot(moves: &[String], go: &Go, rng: &mut StdRng)
+{
+    let mut board = TootAndOtto::new();
+    for mv in moves.iter()
+    {
+        match protocol::parse_toot(mv)
+        {
+            Some((col, letter)) if !board.termination && board.drop(letter, col) => {},
+            _ => {
+                println!("error illegal move {}", mv);
+                return;
+            }
+        }
+    }
+
+    if board.termination
+    {
+        println!("error game is over");
+    }
+    else if let Some(level) = go.level
+    {
+        match toot_ai::ai_next_move(board, Strength::from_level(level), rng)
+        {
+            Some(mv) => println!("bestmove {}", protocol::toot_notation(mv)),
+            None => println!("error game is over"),
+        }
+    }
+    else
+    {
+        report(&toot_ai::analyze(board, go.limit), |mv| protocol::toot_notation(*mv));
+    }
+}
+
+fn main()
+{
+    let mut game = Game::Connect4;
+    let mut moves: Vec<String> = Vec::new();
+    let mut rng = StdRng::seed_from_u64(rand::random());
+
+    let stdin = io::stdin();
+    for line in stdin.lock().lines()
+    {
+        let line = match line
+        {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice()
+        {
+            [] => {},
+            ["quit"] => break,
+            ["isready"] => println!("readyok"),
+            ["seed", seed] => match seed.parse()
+            {
+                Ok(seed) => rng = StdRng::seed_from_u64(seed),
+                Err(_) => println!("error bad seed {}", seed),
+            },
+            ["game", "connect4"] => game = Game::Connect4,
+            ["game", "toot"] => game = Game::Toot,
+            ["position"] => moves.clear(),
+            ["position", "moves", played @ ..] => moves = played.iter().map(|mv| mv.to_string()).collect(),
+            ["go", limits @ ..] => match parse_go(limits)
+            {
+                Some(go) if game == Game::Connect4 => go_connect4(&moves, &go, &mut rng),
+                Some(go) => go_toot(&moves, &go, &mut rng),
+                None => println!("error bad go limits"),
+            },
+            _ => println!("error unknown command {}", line),
+        }
+
+        io::stdout().flush().expect("Error writing to stdout");
+    }
+}
@@ -0,0 +1,2 @@
+// Parts of game_test shared by the menu and the engine binary
+pub mod protocol;
@@ -1,25 +1,29 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use std::io;
-use strum_macros::EnumIter;
 
-#[path = "../../src/connect4/connect4.rs"]
-mod connect4;
+use game_core::connect4::{connect4, con4_ai, con4_model};
 use connect4::{Connect4, C4Piece};
 
-#[path = "../../src/connect4/con4_ai.rs"]
-mod con4_ai;
-
-#[path = "../../src/toot_and_otto/toot_and_otto.rs"]
-mod toot_and_otto;
+use game_core::toot_and_otto::{toot_and_otto, toot_ai, toot_solver};
 use toot_and_otto::{TootAndOtto, Player, TOenum};
 
-#[path = "../../src/toot_and_otto/toot_ai.rs"]
-mod toot_ai;
+use game_core::types::{analysis, eval_weights, review, strength};
+use strength::{Strength, MAX_LEVEL};
+use analysis::{Limit, MoveEval};
+use review::Review;
+use game_test::protocol;
+
+mod bench;
+mod tournament;
+mod training;
+mod tuner;
 
-fn std_catch_C4() -> (usize, bool) 
+fn std_catch_c4() -> (usize, bool) 
 {
     let mut input = String::new();
 
-    if let Err(_) = io::stdin().read_line(&mut input) 
+    if io::stdin().read_line(&mut input).is_err() 
     {
         println!("Invalid input, please try again!");
         return (0, false);
@@ -38,17 +42,17 @@ fn std_catch_C4() -> (usize, bool)
     (input, true)
 }
 
-fn std_catch_OT() -> (char, usize, bool) 
+fn std_catch_ot() -> (char, usize, bool) 
 {
     let mut input = String::new();
 
-    if let Err(_) = io::stdin().read_line(&mut input) 
+    if io::stdin().read_line(&mut input).is_err() 
     {
         println!("Invalid input, please try again!");
         return ('X', 0, false);
     };
 
-    let split_input: Vec<&str> = input.trim().split_whitespace().collect();
+    let split_input: Vec<&str> = input.split_whitespace().collect();
 
     let letter: char = match split_input[0].parse() 
     {
@@ -71,41 +75,76 @@ fn std_catch_OT() -> (char, usize, bool)
     (letter, col, true)
 }
 
-fn game_connect4(oppo : &str) {
-    let mut oppo_choice;
-
+/// Turns the opponent menu choice into an AI level, 0 for a human opponent
+/// and anything above `MAX_LEVEL` for perfect play
+fn opponent_level(oppo : &str) -> usize {
     match oppo 
-    {     
-        "1" => {
-            oppo_choice = 0;
-        },
-        "2" => {
-            oppo_choice = 1;
-        },
-        "3" => {
-            oppo_choice = 2;
-        },
-        "4" => {
-            oppo_choice = 3;
+    {
+        "1" => 0,
+        "2" => 1,
+        "3" => 5,
+        "4" => MAX_LEVEL as usize,
+        "5" => MAX_LEVEL as usize + 1,
+        "6" => {
+            println!("Enter <1-{}>", MAX_LEVEL);
+            match std_catch_c4() 
+            {
+                (level, true) if level >= 1 && level <= MAX_LEVEL as usize => level,
+                _ => {
+                    println!("Invalid Level. Setting to AI - EASY");
+                    1
+                }
+            }
         },
         _ => {
             println!("Invalid Opponent Choice. Setting to AI - EASY");
-            oppo_choice = 1;
+            1
         }
     }
+}
+
+/// Asks for the seed of the AI's random choices, so a game, or a whole run of
+/// AI games, can be replayed exactly by entering the seed it printed
+fn ask_seed() -> u64 
+{
+    println!("Seed (blank for a random one):");
+
+    let mut input = String::new();
+    let seed = match io::stdin().read_line(&mut input).ok().and_then(|_| input.trim().parse().ok()) 
+    {
+        Some(seed) => seed,
+        None => rand::random(),
+    };
+    println!("Seed: {}", seed);
+
+    seed
+}
+
+fn seeded_rng() -> StdRng 
+{
+    StdRng::seed_from_u64(ask_seed())
+}
+
+/// The generator of game `game` in a run seeded with `seed`; every game has its
+/// own, so a game plays the same however much the games before it drew
+pub fn game_rng(seed: u64, game: usize) -> StdRng 
+{
+    StdRng::seed_from_u64(seed.wrapping_add(game as u64))
+}
+
+fn game_connect4(oppo : &str) {
+    let oppo_choice = opponent_level(oppo);
+    let mut rng = seeded_rng();
 
     let mut connect4 = Connect4::initialize();
     let mut player = C4Piece::P2;
+    let mut moves = Vec::new();
 
     println!("{}", connect4);
 
     loop 
     {
-        let winner = match connect4.check_win(player) 
-        {
-            Some(_) => Some(player),
-            None => None,
-        };
+        let winner = connect4.check_win(player).map(|_| player);
 
         if winner.is_some() 
         {
@@ -124,7 +163,7 @@ fn game_connect4(oppo : &str) {
 
             while !validation 
             {
-                let result = std_catch_C4();
+                let result = std_catch_c4();
                 column = result.0;
                 validation = result.1;
 
@@ -133,11 +172,19 @@ fn game_connect4(oppo : &str) {
                     continue;
                 }
                 validation = connect4.place(column);
+                if validation 
+                {
+                    moves.push(column);
+                }
             }
         }
         else 
         {
-            connect4.place(con4_ai::AI_next_move(connect4.clone(), oppo_choice));
+            if let Some(column) = con4_ai::ai_next_move(connect4, Strength::from_level(oppo_choice as f64), con4_ai::classic(), &mut rng) 
+            {
+                connect4.place(column);
+                moves.push(column);
+            }
         }
 
         println!("{}", connect4);
@@ -145,37 +192,21 @@ fn game_connect4(oppo : &str) {
     
     println!("----------------------------------------------------");
     println!("{} Player Won!", player);
-}
 
-fn game_TOTO(oppo : &str) {
+    print_review(&con4_ai::review(&moves, Limit::Depth(4), con4_ai::classic()), |col| col.to_string());
+}
 
-    let mut oppo_choice;
+fn game_toto(oppo : &str) {
 
-    match oppo 
-    {
-        "1" => {
-            oppo_choice = 0;
-        },
-        "2" => {
-            oppo_choice = 1;
-        },
-        "3" => {
-            oppo_choice = 2;
-        },
-        "4" => {
-            oppo_choice = 3;
-        },
-        _ => {
-            println!("Invalid Opponent Choice. Setting to AI - EASY");
-            oppo_choice = 1;
-        }
-    }
+    let oppo_choice = opponent_level(oppo);
+    let mut rng = seeded_rng();
 
-    let mut TOOT = TootAndOtto::new();
+    let mut board = TootAndOtto::new();
 
     let mut player = Player::TOOT;
+    let mut moves = Vec::new();
 
-    println!("{}", TOOT);
+    println!("{}", board);
 
     loop 
     {
@@ -190,7 +221,7 @@ fn game_TOTO(oppo : &str) {
         {
             while !validation 
             {
-                let result = std_catch_OT();
+                let result = std_catch_ot();
                 letter = result.0;
                 column = result.1;
                 validation = result.2;
@@ -211,32 +242,47 @@ fn game_TOTO(oppo : &str) {
                     }
                 };
 
-                validation = TOOT.drop(drop_piece, column);
+                validation = board.drop(drop_piece, column);
 
                 if !validation 
                 {
                     println!("Invalid Input, Try Again");
                 }
+                else 
+                {
+                    moves.push((column, drop_piece));
+                }
 
             }
         } 
         else 
         {
-            let obs = toot_ai::AI_next_move(TOOT.clone(), oppo_choice);
-            TOOT.drop(obs.1, obs.0);
+            let obs = if oppo_choice > MAX_LEVEL as usize 
+            {
+                Some(toot_ai::perfect_move(board))
+            } 
+            else 
+            {
+                toot_ai::ai_next_move(board, Strength::from_level(oppo_choice as f64), &mut rng)
+            };
+            if let Some(obs) = obs 
+            {
+                board.drop(obs.1, obs.0);
+                moves.push(obs);
+            }
         }
 
-        println!("{}", TOOT);
+        println!("{}", board);
 
         player = player.switch();
 
-        if TOOT.termination 
+        if board.termination 
         {
             break;
         }
     }
 
-    match TOOT.winner 
+    match board.winner 
     {
         Some(player) => 
         {
@@ -249,32 +295,564 @@ fn game_TOTO(oppo : &str) {
             println!("Drawn!");
         }
     }
+
+    print_review(&toot_ai::review(&moves, Limit::Depth(3)), |(col, letter)| format!("{}{}", letter, col));
+}
+
+/// Prints every move of a finished game with its verdict and the turning point
+fn print_review<M: PartialEq>(review: &Review<M>, show: impl Fn(&M) -> String) 
+{
+    println!("----------------------------------------------------");
+    println!("Review:");
+
+    for (index, judged) in review.moves.iter().enumerate() 
+    {
+        let best = if judged.best != judged.mv { format!("best was {}", show(&judged.best)) } else { String::new() };
+        let turning_point = if review.turning_point == Some(index) { "<- turning point" } else { "" };
+
+        println!("{:>3}. {:>4}  {:<8} {:<12} {}", index + 1, show(&judged.mv), judged.verdict.to_string(), best, turning_point);
+    }
+}
+
+fn compare_toot_heuristics(games: usize) 
+{
+    let seed = ask_seed();
+    let mut inventory_wins = 0;
+    let mut classic_wins = 0;
+    let mut draws = 0;
+
+    for game in 0..games 
+    {
+        // Swap sides every game so neither evaluator always moves first
+        let inventory_side = if game % 2 == 0 { Player::TOOT } else { Player::OTTO };
+        let mut board = TootAndOtto::new();
+        let mut rng = game_rng(seed, game);
+
+        while !board.termination 
+        {
+            let heuristic = if board.current_player == inventory_side 
+            {
+                toot_ai::inventory()
+            } 
+            else 
+            {
+                toot_ai::Heuristic::Classic(toot_ai::weights())
+            };
+
+            let (col, letter, _) = toot_ai::find_best_move(board, 3, true, board.current_player, heuristic, &mut rng);
+            board.drop(letter, col);
+        }
+
+        match board.winner 
+        {
+            Some(player) if player == inventory_side => inventory_wins += 1,
+            Some(_) => classic_wins += 1,
+            None => draws += 1,
+        }
+    }
+
+    println!("----------------------------------------------------");
+    println!("Inventory vs Classic over {} games (depth 3)", games);
+    println!("Inventory wins: {}", inventory_wins);
+    println!("Classic wins:   {}", classic_wins);
+    println!("Draws:          {}", draws);
+}
+
+/// The opening book compiled into `toot_ai`
+const SHIPPED_BOOK: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../game_core/src/toot_and_otto/toot_book.bin");
+
+/// Solves TOOT and OTTO and reports perfect play, writing the opening book to `book_path` if given
+fn solve_toot(book_path: Option<&str>) 
+{
+    println!("Solving every position with up to {} pieces...", toot_solver::BOOK_PLY);
+
+    let mut solver = toot_solver::Solver::new(24, None);
+    let book = solver.build_book();
+    println!("Solved {} positions ({} nodes searched)", book.len(), solver.nodes);
+
+    if let Some(book_path) = book_path 
+    {
+        if let Err(e) = std::fs::write(book_path, book.to_bytes()) 
+        {
+            println!("Could not write {}: {}", book_path, e);
+            return;
+        }
+        println!("Wrote the book to {}", book_path);
+    }
+
+    let board = TootAndOtto::new();
+    let mut solver = toot_solver::Solver::new(24, Some(book));
+    let describe = |value: i32, player: Player| match value 
+    {
+        1 => format!("{} wins", player),
+        -1 => format!("{} wins", player.switch()),
+        _ => "Draw".to_string(),
+    };
+
+    println!("----------------------------------------------------");
+    println!("Perfect play from the empty board: {}", describe(solver.solve(&board), board.current_player));
+    println!("Opening moves for {}:", board.current_player);
+
+    for (col, letter, value) in solver.move_values(&board) 
+    {
+        println!("  {} {} -> {}", letter, col, describe(value, board.current_player));
+    }
+}
+
+/// Plays one AI vs AI game of Connect 4, returning the first player's points
+fn play_connect4_match(first: Strength, second: Strength, rng: &mut StdRng) -> f64 
+{
+    let heuristic = con4_ai::classic();
+    let mut board = Connect4::initialize();
+
+    while !board.termination 
+    {
+        let strength = if board.current_player == C4Piece::P1 { first } else { second };
+        match con4_ai::ai_next_move(board, strength, heuristic, rng) 
+        {
+            Some(col) => board.place(col),
+            None => break,
+        };
+    }
+
+    match board.winner 
+    {
+        Some(C4Piece::P1) => 1.0,
+        Some(_) => 0.0,
+        None => 0.5,
+    }
+}
+
+/// Plays one AI vs AI game of TOOT and OTTO, returning the first player's points
+fn play_toot_match(first: Strength, second: Strength, rng: &mut StdRng) -> f64 
+{
+    let mut board = TootAndOtto::new();
+
+    while !board.termination 
+    {
+        let strength = if board.current_player == Player::TOOT { first } else { second };
+        match toot_ai::ai_next_move(board, strength, rng) 
+        {
+            Some((col, letter)) => board.drop(letter, col),
+            None => break,
+        };
+    }
+
+    match board.winner 
+    {
+        Some(Player::TOOT) => 1.0,
+        Some(_) => 0.0,
+        None => 0.5,
+    }
+}
+
+/// Fits Bradley-Terry ratings to a table of points scored between players
+/// and returns them on the Elo scale with player 0 anchored at `anchor`.
+/// Every pairing gets one virtual draw so a player without wins stays finite.
+fn fit_elo(points: &[Vec<f64>], played: &[Vec<f64>], anchor: f64) -> Vec<f64> 
+{
+    let players = points.len();
+    let mut gamma = vec![1.0; players];
+
+    for _ in 0..1000 
+    {
+        for i in 0..players 
+        {
+            let wins: f64 = (0..players).filter(|j| *j != i).map(|j| points[i][j] + 0.5).sum();
+            let denominator: f64 = (0..players)
+                .filter(|j| *j != i)
+                .map(|j| (played[i][j] + 1.0) / (gamma[i] + gamma[j]))
+                .sum();
+            gamma[i] = wins / denominator;
+        }
+    }
+
+    gamma.iter().map(|g| anchor + 400.0 * (g / gamma[0]).log10()).collect()
+}
+
+fn calibrate_levels() 
+{
+    println!("Game to calibrate: 1 -- Connect 4, 2 -- TOOT and OTTO");
+    let (game, validation) = std_catch_c4();
+    if !validation || (game != 1 && game != 2) 
+    {
+        println!("Invalid input, please try again!");
+        return;
+    }
+
+    println!("Games per pairing:");
+    let (games, validation) = std_catch_c4();
+    if !validation 
+    {
+        return;
+    }
+    let seed = ask_seed();
+
+    let levels = MAX_LEVEL as usize;
+    let mut points = vec![vec![0.0; levels]; levels];
+    let mut played = vec![vec![0.0; levels]; levels];
+
+    for i in 0..levels 
+    {
+        for j in i + 1..levels 
+        {
+            for game_index in 0..games 
+            {
+                // Alternate colours so neither level always moves first
+                let (first, second) = if game_index % 2 == 0 { (i, j) } else { (j, i) };
+                let first_strength = Strength::from_level((first + 1) as f64);
+                let second_strength = Strength::from_level((second + 1) as f64);
+                let mut rng = game_rng(seed, (i * levels + j) * games + game_index);
+
+                let result = if game == 1 
+                {
+                    play_connect4_match(first_strength, second_strength, &mut rng)
+                } 
+                else 
+                {
+                    play_toot_match(first_strength, second_strength, &mut rng)
+                };
+
+                points[first][second] += result;
+                points[second][first] += 1.0 - result;
+                played[first][second] += 1.0;
+                played[second][first] += 1.0;
+            }
+        }
+        println!("Level {} done", i + 1);
+    }
+
+    let elo = fit_elo(&points, &played, 800.0);
+    let current = if game == 1 { con4_ai::LEVEL_ELO } else { toot_ai::LEVEL_ELO };
+
+    println!("----------------------------------------------------");
+    println!("Level  Depth  Temp   Blunder  Score   Elo   (table)");
+
+    for level in 0..levels 
+    {
+        let strength = Strength::from_level((level + 1) as f64);
+        let depth = if game == 1 { strength.depth } else { strength.depth.min(toot_ai::MAX_DEPTH) };
+        let score: f64 = points[level].iter().sum();
+        let total: f64 = played[level].iter().sum();
+
+        println!(
+            "{:>5}  {:>5}  {:>5.1}  {:>7.2}  {:>5.1}%  {:>4.0}  ({})",
+            level + 1, depth, strength.temperature, strength.blunder,
+            100.0 * score / total, elo[level], current[level]
+        );
+    }
+}
+
+/// One line of the analysis table: move, score, mate distance and principal variation
+fn describe_eval<M>(eval: &MoveEval<M>, show: impl Fn(&M) -> String) -> String 
+{
+    let result = match eval.mate 
+    {
+        Some(plies) if plies > 0 => format!("wins in {}", plies),
+        Some(plies) => format!("loses in {}", -plies),
+        None => eval.score.to_string(),
+    };
+    let pv: Vec<String> = eval.pv.iter().map(&show).collect();
+
+    format!("{:>4}  {:>12}  {}", show(&eval.mv), result, pv.join(" "))
+}
+
+fn analyze_position() 
+{
+    println!("1 -- Connect 4 (moves as columns, e.g. 3 3 4)");
+    println!("2 -- TOOT and OTTO (moves as letter and column, e.g. T0 O3)");
+    let (game, _) = std_catch_c4();
+
+    println!("Moves played so far:");
+    let mut moves = String::new();
+    if io::stdin().read_line(&mut moves).is_err() 
+    {
+        println!("Invalid input, please try again!");
+        return;
+    };
+
+    println!("Search depth in plies, or 0 to think for one second:");
+    let (depth, validation) = std_catch_c4();
+    if !validation 
+    {
+        return;
+    }
+    let limit = if depth == 0 { Limit::Millis(1000) } else { Limit::Depth(depth) };
+
+    println!("----------------------------------------------------");
+    if game == 1 
+    {
+        let mut board = Connect4::initialize();
+        for mv in moves.split_whitespace() 
+        {
+            match protocol::parse_connect4(mv) 
+            {
+                Some(col) if board.place(col) => {},
+                _ => {
+                    println!("Illegal move {}", mv);
+                    return;
+                }
+            }
+        }
+        println!("{}", board);
+
+        let analysis = con4_ai::analyze(board, limit, con4_ai::classic());
+        println!("{} to move, searched {} plies", board.current_player, analysis.depth);
+        for eval in analysis.moves.iter() 
+        {
+            println!("{}", describe_eval(eval, |col| protocol::connect4_notation(*col)));
+        }
+    }
+    else 
+    {
+        let mut board = TootAndOtto::new();
+        for mv in moves.split_whitespace() 
+        {
+            match protocol::parse_toot(mv) 
+            {
+                Some((col, letter)) if board.drop(letter, col) => {},
+                _ => {
+                    println!("Illegal move {}", mv);
+                    return;
+                }
+            }
+        }
+        println!("{}", board);
+
+        let analysis = toot_ai::analyze(board, limit);
+        println!("{} to move, searched {} plies", board.current_player, analysis.depth);
+        for eval in analysis.moves.iter() 
+        {
+            println!("{}", describe_eval(eval, |mv| protocol::toot_notation(*mv)));
+        }
+    }
+}
+
+fn run_tournament() 
+{
+    println!("Game: 1 -- Connect 4, 2 -- TOOT and OTTO");
+    let (game, validation) = std_catch_c4();
+    if !validation || (game != 1 && game != 2) 
+    {
+        println!("Invalid input, please try again!");
+        return;
+    }
+    let toot = game == 2;
+
+    println!("Engines, separated by spaces (e.g. level3 level10 minimax4 mcts2000 random ext:./engine{}):", if toot { " classic4 perfect" } else { " learned4" });
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() 
+    {
+        println!("Invalid input, please try again!");
+        return;
+    };
+
+    let mut engines = Vec::new();
+    for spec in input.split_whitespace() 
+    {
+        match tournament::Engine::parse(spec, toot) 
+        {
+            Some(engine) => engines.push(engine),
+            None => {
+                println!("Unknown engine {}", spec);
+                return;
+            }
+        }
+    }
+    if engines.len() < 2 
+    {
+        println!("A tournament needs at least two engines");
+        return;
+    }
+
+    println!("Games per pairing:");
+    let (games, validation) = std_catch_c4();
+    if !validation 
+    {
+        return;
+    }
+
+    tournament::round_robin(&engines, games, toot, ask_seed());
+}
+
+fn tune_weights() 
+{
+    println!("Game: 1 -- Connect 4, 2 -- TOOT and OTTO");
+    let (game, validation) = std_catch_c4();
+    if !validation || (game != 1 && game != 2) 
+    {
+        println!("Invalid input, please try again!");
+        return;
+    }
+    let toot = game == 2;
+
+    println!("Rounds:");
+    let (rounds, validation) = std_catch_c4();
+    if !validation 
+    {
+        return;
+    }
+
+    println!("Game pairs per round:");
+    let (pairs, validation) = std_catch_c4();
+    if !validation || pairs == 0 
+    {
+        return;
+    }
+
+    let weights = tuner::tune(toot, rounds, pairs, ask_seed());
+    println!("{}", weights.to_json());
+
+    println!("Save these weights for the client? (y/n)");
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_ok() && answer.trim() == "y" 
+    {
+        match tuner::save(&weights, toot) 
+        {
+            Ok(path) => println!("Saved to {}", path),
+            Err(err) => println!("Could not save the weights: {}", err),
+        }
+    }
+}
+
+fn train_evaluation() 
+{
+    println!("Self-play games:");
+    let (games, validation) = std_catch_c4();
+    if !validation || games == 0 
+    {
+        return;
+    }
+    let seed = ask_seed();
+    let model = training::train(games, seed);
+
+    println!("Benchmark game pairs against the classic heuristic:");
+    let (pairs, validation) = std_catch_c4();
+    if !validation 
+    {
+        return;
+    }
+    training::benchmark(&model, pairs, seed);
+
+    println!("Save this model for the client? (y/n)");
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer).is_ok() && answer.trim() == "y" 
+    {
+        match training::save(&model) 
+        {
+            Ok(path) => println!("Saved to {}", path),
+            Err(err) => println!("Could not save the model: {}", err),
+        }
+    }
 }
 
 fn main() 
 {
+    // Subcommands that write files; the menu below only reports
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(|arg| arg.as_str()) 
+    {
+        Some("solve-toot") => return solve_toot(args.get(2).map(|path| path.as_str())),
+        Some("regenerate-book") => return solve_toot(Some(SHIPPED_BOOK)),
+        Some(command) => 
+        {
+            println!("Unknown command {}; the commands are solve-toot [<book path>] and regenerate-book", command);
+            return;
+        },
+        None => {},
+    }
 
     println!("Please choose following game to play: ");
     println!("1 -- Connect 4");
     println!("2 -- TOOT and OTTO");
+    println!("3 -- TOOT and OTTO evaluator comparison (AI vs AI)");
+    println!("4 -- Solve TOOT and OTTO (perfect play report)");
+    println!("5 -- Calibrate AI levels (Elo by self-play)");
+    println!("6 -- Analyze a position");
+    println!("7 -- AI tournament (round robin)");
+    println!("8 -- Tune evaluation weights (self-play)");
+    println!("9 -- Benchmark the Connect 4 search");
+    println!("10 -- Train the learned Connect 4 evaluation");
 
     let mut input = String::new();
 
-    if let Err(_) = io::stdin().read_line(&mut input) 
+    if io::stdin().read_line(&mut input).is_err() 
     {
         println!("Input failed, try again");
         return;
     };
 
+    if input.trim() == "3" 
+    {
+        println!("Number of games:");
+
+        let (games, validation) = std_catch_c4();
+        if validation 
+        {
+            compare_toot_heuristics(games);
+        }
+        return;
+    }
+
+    if input.trim() == "4" 
+    {
+        solve_toot(None);
+        return;
+    }
+
+    if input.trim() == "5" 
+    {
+        calibrate_levels();
+        return;
+    }
+
+    if input.trim() == "6" 
+    {
+        analyze_position();
+        return;
+    }
+
+    if input.trim() == "7" 
+    {
+        run_tournament();
+        return;
+    }
+
+    if input.trim() == "8" 
+    {
+        tune_weights();
+        return;
+    }
+
+    if input.trim() == "9" 
+    {
+        println!("Deepest search depth:");
+
+        let (depth, validation) = std_catch_c4();
+        if validation && depth >= 1 
+        {
+            bench::benchmark(depth);
+        }
+        return;
+    }
+
+    if input.trim() == "10" 
+    {
+        train_evaluation();
+        return;
+    }
+
     println!("--------------------------------------------------");
     println!("1. HUMAN");
-    println!("2. AI - EASY");
-    println!("3. AI - MEDIUM");
-    println!("4. AI - EXPERT");
+    println!("2. AI - EASY (level 1)");
+    println!("3. AI - MEDIUM (level 5)");
+    println!("4. AI - EXPERT (level {})", MAX_LEVEL);
+    println!("5. AI - EXPERT+ (TOOT and OTTO only)");
+    println!("6. AI - CHOOSE A LEVEL");
 
     let mut oppo = String::new();
 
-    if let Err(_) = io::stdin().read_line(&mut oppo) 
+    if io::stdin().read_line(&mut oppo).is_err() 
     {
         println!("Invalid input, please try again!");    
         return;
@@ -288,7 +866,7 @@ fn main()
     } 
     else if input == "2" 
     {
-        game_TOTO(oppo.trim());
+        game_toto(oppo.trim());
     }
     else
     {
@@ -0,0 +1,123 @@
+/*
+Line-based engine protocol, spoken over an engine's stdin and stdout:
+
+    game connect4 | game toot      pick the game, Connect 4 by default
+    isready                        engine replies "readyok"
+    seed <n>                       seed the random choices of "go level", to replay a game
+    position [moves <m> <m> ...]   the position after these moves from the empty board
+    go [movetime <ms>] [depth <plies>] [level <1-10>]
+                                   engine replies "bestmove <m>", optionally after
+                                   "info depth <d> score <s> [mate <plies>] pv <m> ..."
+    quit
+
+Connect 4 moves are a column (0-6), TOOT and OTTO moves a letter and a column (T0, O5).
+Engines reply "error <reason>" to anything they cannot follow.
+*/
+// The move notation is shared with the game records the server checks
+pub use game_core::record::{connect4_notation, parse_connect4, toot_notation, parse_toot};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long an engine may take to start up and answer "isready"
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time an engine may take past its movetime before it loses on time
+const MOVE_GRACE: Duration = Duration::from_secs(1);
+
+/// An engine process driven through the protocol
+pub struct ExternalEngine
+{
+    child: Child,
+    input: ChildStdin,
+    /// Lines of the engine's output, read on their own thread so a silent engine can be timed out
+    output: Receiver<String>,
+}
+
+impl ExternalEngine
+{
+    /// Starts `command` and waits until it is ready to play `game`
+    pub fn spawn(command: &str, game: &str) -> io::Result<Self>
+    {
+        let mut child = Command::new(command).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let input = child.stdin.take().unwrap();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+
+        let (lines, output) = mpsc::channel();
+        thread::spawn(move || {
+            for line in stdout.lines().map_while(Result::ok)
+            {
+                if lines.send(line).is_err()
+                {
+                    break;
+                }
+            }
+        });
+        let mut engine = ExternalEngine { child, input, output };
+
+        engine.send(&format!("game {}", game))?;
+        engine.send("isready")?;
+        engine.wait_for("readyok", READY_TIMEOUT)?;
+
+        Ok(engine)
+    }
+
+    /// Asks for a move in the position after `moves`, returning its notation;
+    /// an engine that has not answered shortly after `movetime` fails with `TimedOut`
+    pub fn best_move(&mut self, moves: &[String], movetime: u64) -> io::Result<String>
+    {
+        self.send(&format!("position moves {}", moves.join(" ")))?;
+        self.send(&format!("go movetime {}", movetime))?;
+
+        let line = self.wait_for("bestmove", Duration::from_millis(movetime) + MOVE_GRACE)?;
+        Ok(line.trim_start_matches("bestmove").trim().to_string())
+    }
+
+    fn send(&mut self, line: &str) -> io::Result<()>
+    {
+        writeln!(self.input, "{}", line)?;
+        self.input.flush()
+    }
+
+    /// Reads lines until one starts with `prefix`, failing on "error", end of output
+    /// or `timeout` passing first; an engine that times out is killed
+    fn wait_for(&mut self, prefix: &str, timeout: Duration) -> io::Result<String>
+    {
+        let deadline = Instant::now() + timeout;
+        loop
+        {
+            let line = match self.output.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    let _ = self.child.kill();
+                    return Err(io::Error::new(io::ErrorKind::TimedOut, format!("no \"{}\" within {} ms", prefix, timeout.as_millis())));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "engine closed its output"));
+                }
+            };
+
+            let line = line.trim();
+            if line.starts_with(prefix)
+            {
+                return Ok(line.to_string());
+            }
+            else if line.starts_with("error")
+            {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, line.to_string()));
+            }
+        }
+    }
+}
+
+impl Drop for ExternalEngine
+{
+    fn drop(&mut self)
+    {
+        let _ = self.send("quit");
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,557 @@
+/*
+Round-robin tournaments between AI engines, with a small Monte Carlo tree
+search so the minimax AIs have something different to play against
+*/
+use crate::connect4::{Connect4, C4Piece};
+use crate::toot_and_otto::{TootAndOtto, Player, TOenum};
+use crate::toot_ai::Heuristic;
+use game_test::protocol::{self, ExternalEngine};
+use crate::{con4_ai, fit_elo, game_rng, toot_ai, Strength};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt::{Display, Formatter, Result};
+use std::io;
+use std::path::Path;
+
+/// UCB1 exploration constant of the tree search
+const EXPLORATION: f64 = 1.4;
+
+/// Average rating of the field in the printed Elo column
+const FIELD_ELO: f64 = 1500.0;
+
+/// Thinking time given to external engines for every move
+const EXTERNAL_MOVETIME: u64 = 500;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Engine
+{
+    /// The page AI at a (possibly fractional) level
+    Level(f64),
+    /// Full-strength minimax to a fixed depth, no softmax and no blunders
+    Minimax(usize),
+    /// TOOT and OTTO minimax with the original window-counting heuristic
+    Classic(usize),
+    /// Connect 4 minimax to a fixed depth with the learned evaluation
+    Learned(usize),
+    /// Monte Carlo tree search with this many random playouts a move
+    Mcts(usize),
+    /// TOOT and OTTO solver
+    Perfect,
+    Random,
+    /// A program speaking the engine protocol, started once per game
+    External(String),
+}
+
+impl Engine
+{
+    /// Reads an engine such as `level7`, `minimax4`, `mcts2000`, `random` or
+    /// `ext:<program>`; `classic4` and `perfect` are only offered for TOOT and OTTO,
+    /// `learned4` only for Connect 4
+    pub fn parse(spec: &str, toot: bool) -> Option<Engine>
+    {
+        if let Some(command) = spec.strip_prefix("ext:")
+        {
+            return Some(Engine::External(command.to_string()));
+        }
+
+        let digits = spec.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+        let name = &spec[..spec.len() - digits.len()];
+
+        match (name, toot)
+        {
+            ("random", _) if digits.is_empty() => Some(Engine::Random),
+            ("perfect", true) if digits.is_empty() => Some(Engine::Perfect),
+            ("level", _) => digits.parse().ok().filter(|level| *level >= 1.0).map(Engine::Level),
+            ("minimax", _) => digits.parse().ok().filter(|depth| *depth >= 1).map(Engine::Minimax),
+            ("classic", true) => digits.parse().ok().filter(|depth| *depth >= 1).map(Engine::Classic),
+            ("learned", false) => digits.parse().ok().filter(|depth| *depth >= 1).map(Engine::Learned),
+            ("mcts", _) => digits.parse().ok().filter(|playouts| *playouts >= 1).map(Engine::Mcts),
+            _ => None,
+        }
+    }
+
+    fn connect4_move(&self, board: Connect4, rng: &mut StdRng) -> Option<usize>
+    {
+        match *self
+        {
+            Engine::Level(level) => con4_ai::ai_next_move(board, Strength::from_level(level), con4_ai::classic(), rng),
+            Engine::Minimax(depth) => con4_ai::ai_next_move(board, exact(depth), con4_ai::classic(), rng),
+            Engine::Learned(depth) => con4_ai::ai_next_move(board, exact(depth), con4_ai::learned(), rng),
+            Engine::Mcts(playouts) => Some(mcts(board, playouts, rng)),
+            Engine::Random => con4_ai::random_move(board, rng),
+            Engine::Classic(_) | Engine::Perfect => unreachable!("only parsed for TOOT and OTTO"),
+            Engine::External(_) => unreachable!("external engines play through a Seat"),
+        }
+    }
+
+    fn toot_move(&self, board: TootAndOtto, rng: &mut StdRng) -> Option<(usize, TOenum)>
+    {
+        match *self
+        {
+            Engine::Level(level) => toot_ai::ai_next_move(board, Strength::from_level(level), rng),
+            Engine::Minimax(depth) => toot_ai::ai_next_move(board, exact(depth), rng),
+            Engine::Classic(depth) =>
+            {
+                let scores = toot_ai::score_moves(board, depth, Heuristic::Classic(toot_ai::weights()), rng);
+                let values: Vec<i32> = scores.iter().map(|(_, _, value)| *value).collect();
+                exact(depth).choose(&values, rng).map(|index| (scores[index].0, scores[index].1))
+            },
+            Engine::Mcts(playouts) => Some(mcts(board, playouts, rng)),
+            Engine::Perfect => Some(toot_ai::perfect_move(board)),
+            Engine::Learned(_) => unreachable!("only parsed for Connect 4"),
+            Engine::Random => toot_ai::random_move(board, rng),
+            Engine::External(_) => unreachable!("external engines play through a Seat"),
+        }
+    }
+}
+
+impl Display for Engine
+{
+    fn fmt(&self, f: &mut Formatter) -> Result
+    {
+        match self
+        {
+            Engine::Level(level) => write!(f, "level{}", level),
+            Engine::Minimax(depth) => write!(f, "minimax{}", depth),
+            Engine::Classic(depth) => write!(f, "classic{}", depth),
+            Engine::Learned(depth) => write!(f, "learned{}", depth),
+            Engine::Mcts(playouts) => write!(f, "mcts{}", playouts),
+            Engine::Perfect => write!(f, "perfect"),
+            Engine::Random => write!(f, "random"),
+            // Just the program name, so the tables stay narrow
+            Engine::External(command) => match Path::new(command).file_name()
+            {
+                Some(name) => write!(f, "{}", name.to_string_lossy()),
+                None => write!(f, "{}", command),
+            },
+        }
+    }
+}
+
+/// A strength that always plays one of the best moves at `depth`
+pub fn exact(depth: usize) -> Strength
+{
+    Strength { depth, temperature: 0.0, blunder: 0.0 }
+}
+
+/// What the tree search needs to know about a game
+trait Game: Copy
+{
+    type Move: Copy + PartialEq;
+
+    /// Game name in the engine protocol
+    const NAME: &'static str;
+
+    fn notation(mv: Self::Move) -> String;
+    fn parse_move(text: &str) -> Option<Self::Move>;
+    /// The move of a builtin engine, `None` when it has none to play
+    fn builtin_move(&self, engine: &Engine, rng: &mut StdRng) -> Option<Self::Move>;
+
+    /// Legal moves, none once the game is over
+    fn moves(&self) -> Vec<Self::Move>;
+    fn play(&mut self, mv: Self::Move);
+    fn first_to_move(&self) -> bool;
+    fn over(&self) -> bool;
+    /// Points of the player who moved first, once the game is over
+    fn first_points(&self) -> f64;
+}
+
+impl Game for Connect4
+{
+    type Move = usize;
+
+    const NAME: &'static str = "connect4";
+
+    fn notation(mv: usize) -> String
+    {
+        protocol::connect4_notation(mv)
+    }
+
+    fn parse_move(text: &str) -> Option<usize>
+    {
+        protocol::parse_connect4(text)
+    }
+
+    fn builtin_move(&self, engine: &Engine, rng: &mut StdRng) -> Option<usize>
+    {
+        engine.connect4_move(*self, rng)
+    }
+
+    fn moves(&self) -> Vec<usize>
+    {
+        if self.termination { Vec::new() } else { self.get_availiable_columns() }
+    }
+
+    fn play(&mut self, mv: usize)
+    {
+        self.place(mv);
+    }
+
+    fn first_to_move(&self) -> bool
+    {
+        self.current_player == C4Piece::P1
+    }
+
+    fn over(&self) -> bool
+    {
+        self.termination
+    }
+
+    fn first_points(&self) -> f64
+    {
+        match self.winner
+        {
+            Some(C4Piece::P1) => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }
+}
+
+impl Game for TootAndOtto
+{
+    type Move = (usize, TOenum);
+
+    const NAME: &'static str = "toot";
+
+    fn notation(mv: (usize, TOenum)) -> String
+    {
+        protocol::toot_notation(mv)
+    }
+
+    fn parse_move(text: &str) -> Option<(usize, TOenum)>
+    {
+        protocol::parse_toot(text)
+    }
+
+    fn builtin_move(&self, engine: &Engine, rng: &mut StdRng) -> Option<(usize, TOenum)>
+    {
+        engine.toot_move(*self, rng)
+    }
+
+    fn moves(&self) -> Vec<(usize, TOenum)>
+    {
+        self.legal_moves()
+    }
+
+    fn play(&mut self, mv: (usize, TOenum))
+    {
+        self.drop(mv.1, mv.0);
+    }
+
+    fn first_to_move(&self) -> bool
+    {
+        self.current_player == Player::TOOT
+    }
+
+    fn over(&self) -> bool
+    {
+        self.termination
+    }
+
+    fn first_points(&self) -> f64
+    {
+        match self.winner
+        {
+            Some(Player::TOOT) => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }
+}
+
+struct Node<M>
+{
+    mv: Option<M>,
+    parent: usize,
+    children: Vec<usize>,
+    untried: Vec<M>,
+    /// Whether the first player made the move into this node
+    by_first: bool,
+    visits: f64,
+    /// Points scored in playouts through this node by the player who moved into it
+    points: f64,
+}
+
+fn uct<M>(node: &Node<M>, parent_visits: f64) -> f64
+{
+    node.points / node.visits + EXPLORATION * (parent_visits.ln() / node.visits).sqrt()
+}
+
+/// UCT search from `root`, returning its most visited move
+fn mcts<G: Game>(root: G, playouts: usize, rng: &mut StdRng) -> G::Move
+{
+    let mut nodes = vec![Node {
+        mv: None,
+        parent: 0,
+        children: Vec::new(),
+        untried: root.moves(),
+        by_first: !root.first_to_move(),
+        visits: 0.0,
+        points: 0.0,
+    }];
+
+    for _ in 0..playouts
+    {
+        let mut index = 0;
+        let mut game = root;
+
+        // Selection
+        while nodes[index].untried.is_empty() && !nodes[index].children.is_empty()
+        {
+            let parent_visits = nodes[index].visits;
+            index = *nodes[index]
+                .children
+                .iter()
+                .max_by(|a, b| uct(&nodes[**a], parent_visits).partial_cmp(&uct(&nodes[**b], parent_visits)).unwrap())
+                .unwrap();
+            game.play(nodes[index].mv.unwrap());
+        }
+
+        // Expansion
+        if !nodes[index].untried.is_empty()
+        {
+            let pick = rng.gen_range(0, nodes[index].untried.len());
+            let mv = nodes[index].untried.swap_remove(pick);
+            let by_first = game.first_to_move();
+            game.play(mv);
+
+            nodes.push(Node { mv: Some(mv), parent: index, children: Vec::new(), untried: game.moves(), by_first, visits: 0.0, points: 0.0 });
+            let child = nodes.len() - 1;
+            nodes[index].children.push(child);
+            index = child;
+        }
+
+        // Random playout
+        while !game.over()
+        {
+            let mv = *game.moves().choose(rng).unwrap();
+            game.play(mv);
+        }
+        let first_points = game.first_points();
+
+        // Backpropagation
+        loop
+        {
+            let node = &mut nodes[index];
+            node.visits += 1.0;
+            node.points += if node.by_first { first_points } else { 1.0 - first_points };
+
+            if index == 0
+            {
+                break;
+            }
+            index = node.parent;
+        }
+    }
+
+    let best = nodes[0]
+        .children
+        .iter()
+        .max_by(|a, b| nodes[**a].visits.partial_cmp(&nodes[**b].visits).unwrap())
+        .unwrap();
+    nodes[*best].mv.unwrap()
+}
+
+/// An engine sitting down for one game; external ones get their own process
+enum Seat<'a>
+{
+    Builtin(&'a Engine),
+    External(ExternalEngine),
+}
+
+impl<'a> Seat<'a>
+{
+    fn new<G: Game>(engine: &'a Engine) -> io::Result<Seat<'a>>
+    {
+        match engine
+        {
+            Engine::External(command) => Ok(Seat::External(ExternalEngine::spawn(command, G::NAME)?)),
+            engine => Ok(Seat::Builtin(engine)),
+        }
+    }
+
+    /// The seat's move after `history`, or why it could not give a legal one
+    fn next_move<G: Game>(&mut self, board: G, history: &[String], rng: &mut StdRng) -> io::Result<G::Move>
+    {
+        let mv = match self
+        {
+            Seat::Builtin(engine) => match board.builtin_move(engine, rng)
+            {
+                Some(mv) => mv,
+                None => return Err(io::Error::new(io::ErrorKind::InvalidData, "no move to play")),
+            },
+            Seat::External(process) =>
+            {
+                let reply = process.best_move(history, EXTERNAL_MOVETIME)?;
+                match G::parse_move(&reply)
+                {
+                    Some(mv) => mv,
+                    None => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unreadable move {}", reply))),
+                }
+            },
+        };
+
+        if board.moves().contains(&mv)
+        {
+            Ok(mv)
+        }
+        else
+        {
+            Err(io::Error::new(io::ErrorKind::InvalidData, format!("illegal move {}", G::notation(mv))))
+        }
+    }
+}
+
+/// Plays one game between two engines, returning the first engine's points;
+/// an engine that fails to start or plays an illegal move loses
+fn play_game<G: Game>(mut board: G, first: &Engine, second: &Engine, rng: &mut StdRng) -> f64
+{
+    let mut seats = Vec::new();
+    for (index, engine) in [first, second].iter().enumerate()
+    {
+        match Seat::new::<G>(engine)
+        {
+            Ok(seat) => seats.push(seat),
+            Err(e) => {
+                println!("{} forfeits: {}", engine, e);
+                return index as f64;
+            }
+        }
+    }
+
+    let mut history = Vec::new();
+    while !board.over()
+    {
+        let seat = if board.first_to_move() { 0 } else { 1 };
+
+        match seats[seat].next_move(board, &history, rng)
+        {
+            Ok(mv) => {
+                history.push(G::notation(mv));
+                board.play(mv);
+            },
+            Err(e) => {
+                println!("{} forfeits: {}", if seat == 0 { first } else { second }, e);
+                return seat as f64;
+            }
+        }
+    }
+
+    board.first_points()
+}
+
+/// 95% confidence half-width of each rating, from the Fisher information
+/// of the Bradley-Terry model at the fitted ratings
+fn elo_margins(elo: &[f64], played: &[Vec<f64>]) -> Vec<f64>
+{
+    let per_elo = 10f64.ln() / 400.0;
+
+    (0..elo.len())
+        .map(|i| {
+            let information: f64 = (0..elo.len())
+                .filter(|j| *j != i)
+                .map(|j| {
+                    let expected = 1.0 / (1.0 + 10f64.powf((elo[j] - elo[i]) / 400.0));
+                    played[i][j] * expected * (1.0 - expected)
+                })
+                .sum();
+
+            1.96 / (per_elo * information.sqrt())
+        })
+        .collect()
+}
+
+/// Plays `games` games between every pair of `engines`, alternating colours,
+/// and prints the results and rating estimates. Each game draws from its own
+/// generator derived from `seed`, so the builtin engines replay it exactly
+pub fn round_robin(engines: &[Engine], games: usize, toot: bool, seed: u64)
+{
+    let count = engines.len();
+    let mut wins = vec![vec![0.0; count]; count];
+    let mut draws = vec![vec![0.0; count]; count];
+    let mut played = vec![vec![0.0; count]; count];
+
+    for i in 0..count
+    {
+        for j in i + 1..count
+        {
+            for game_index in 0..games
+            {
+                let (first, second) = if game_index % 2 == 0 { (i, j) } else { (j, i) };
+                let mut rng = game_rng(seed, (i * count + j) * games + game_index);
+                let result = if toot
+                {
+                    play_game(TootAndOtto::new(), &engines[first], &engines[second], &mut rng)
+                }
+                else
+                {
+                    play_game(Connect4::initialize(), &engines[first], &engines[second], &mut rng)
+                };
+
+                if result == 1.0
+                {
+                    wins[first][second] += 1.0;
+                }
+                else if result == 0.0
+                {
+                    wins[second][first] += 1.0;
+                }
+                else
+                {
+                    draws[first][second] += 1.0;
+                    draws[second][first] += 1.0;
+                }
+                played[first][second] += 1.0;
+                played[second][first] += 1.0;
+            }
+            println!("{} vs {}: {}-{}-{}", engines[i], engines[j], wins[i][j], draws[i][j], wins[j][i]);
+        }
+    }
+
+    let points: Vec<Vec<f64>> = (0..count)
+        .map(|i| (0..count).map(|j| wins[i][j] + 0.5 * draws[i][j]).collect())
+        .collect();
+    let fitted = fit_elo(&points, &played, 0.0);
+    let mean = fitted.iter().sum::<f64>() / count as f64;
+    let elo: Vec<f64> = fitted.iter().map(|rating| rating - mean + FIELD_ELO).collect();
+    let margins = elo_margins(&elo, &played);
+
+    println!("----------------------------------------------------");
+    println!("Wins-draws-losses of each row against each column:");
+    print!("{:>12}", "");
+    for engine in engines.iter()
+    {
+        print!("{:>12}", engine.to_string());
+    }
+    println!();
+    for i in 0..count
+    {
+        print!("{:>12}", engines[i].to_string());
+        for j in 0..count
+        {
+            let cell = if i == j { "-".to_string() } else { format!("{}-{}-{}", wins[i][j], draws[i][j], wins[j][i]) };
+            print!("{:>12}", cell);
+        }
+        println!();
+    }
+
+    println!("----------------------------------------------------");
+    println!("{:>12}  {:>5}  {:>5}  {:>5}  {:>6}  {:>5}  {:>7}", "Engine", "W", "D", "L", "Score", "Elo", "95% CI");
+
+    let mut order: Vec<usize> = (0..count).collect();
+    order.sort_by(|a, b| elo[*b].partial_cmp(&elo[*a]).unwrap());
+    for i in order
+    {
+        let won: f64 = wins[i].iter().sum();
+        let drawn: f64 = draws[i].iter().sum();
+        let total: f64 = played[i].iter().sum();
+
+        println!(
+            "{:>12}  {:>5}  {:>5}  {:>5}  {:>5.1}%  {:>5.0}  +/-{:<4.0}",
+            engines[i].to_string(), won, drawn, total - won - drawn,
+            100.0 * (won + 0.5 * drawn) / total, elo[i], margins[i]
+        );
+    }
+}
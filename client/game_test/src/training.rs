@@ -0,0 +1,197 @@
+/*
+Training of the learned Connect 4 evaluation (`con4_model`): logistic regression
+on the positions of self-play games, labelled with how each game ended
+*/
+use crate::con4_ai::{self, Heuristic};
+use crate::con4_model::{features, LinearModel};
+use crate::connect4::{Connect4, C4Piece};
+use crate::tuner::connect4_pair;
+use crate::{game_rng, Strength};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::fs;
+use std::time::Instant;
+
+/// Random plies at the start of every game, so the games differ
+const OPENING_PLIES: usize = 4;
+
+/// Range of the levels playing the games, so weak and strong play are both covered
+const LOWEST_LEVEL: f64 = 3.0;
+const HIGHEST_LEVEL: f64 = 10.0;
+
+/// Share of the games kept out of training to measure the model on
+const HELD_OUT: f64 = 0.1;
+
+const EPOCHS: usize = 30;
+const LEARNING_RATE: f64 = 0.005;
+const L2: f64 = 1e-4;
+
+/// Search depth of both sides in the benchmark games
+const MATCH_DEPTH: usize = 4;
+
+/// Depth of the timed search in the benchmark
+const SPEED_DEPTH: usize = 5;
+
+/// Where the client reads the model from
+const MODEL_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../game_core/src/connect4/con4_model.json");
+
+struct Sample
+{
+    features: Vec<f64>,
+    /// Points the side the features are taken for scored in the end
+    outcome: f64,
+}
+
+/// Plays one self-play game, returning every position in it from both sides
+fn self_play<R: Rng>(rng: &mut R) -> Vec<Sample>
+{
+    let heuristic = con4_ai::classic();
+    let levels = [rng.gen_range(LOWEST_LEVEL, HIGHEST_LEVEL), rng.gen_range(LOWEST_LEVEL, HIGHEST_LEVEL)];
+
+    let mut board = Connect4::initialize();
+    let mut positions = Vec::new();
+    while !board.termination
+    {
+        let col = if board.next_step < OPENING_PLIES
+        {
+            con4_ai::random_move(board, rng)
+        }
+        else
+        {
+            let level = if board.current_player == C4Piece::P1 { levels[0] } else { levels[1] };
+            con4_ai::ai_next_move(board, Strength::from_level(level), heuristic, rng)
+        };
+        match col
+        {
+            Some(col) => board.place(col),
+            None => break,
+        };
+
+        if !board.termination
+        {
+            positions.push(board);
+        }
+    }
+
+    let first_points = match board.winner
+    {
+        Some(C4Piece::P1) => 1.0,
+        Some(_) => 0.0,
+        None => 0.5,
+    };
+
+    positions.iter().flat_map(|position| vec![
+        Sample { features: features(position, C4Piece::P1), outcome: first_points },
+        Sample { features: features(position, C4Piece::P2), outcome: 1.0 - first_points },
+    ]).collect()
+}
+
+fn sigmoid(logit: f64) -> f64
+{
+    1.0 / (1.0 + (-logit).exp())
+}
+
+/// Mean log loss, and the share of decided positions whose winner the model favours
+fn assess(model: &LinearModel, samples: &[Sample]) -> (f64, f64)
+{
+    let mut loss = 0.0;
+    let mut decided = 0;
+    let mut favoured = 0;
+
+    for sample in samples.iter()
+    {
+        let p = sigmoid(model.logit(&sample.features)).clamp(1e-9, 1.0 - 1e-9);
+        loss -= sample.outcome * p.ln() + (1.0 - sample.outcome) * (1.0 - p).ln();
+
+        if sample.outcome != 0.5
+        {
+            decided += 1;
+            if (p > 0.5) == (sample.outcome == 1.0)
+            {
+                favoured += 1;
+            }
+        }
+    }
+
+    (loss / samples.len().max(1) as f64, favoured as f64 / decided.max(1) as f64)
+}
+
+/// Stochastic gradient descent on the log loss
+fn fit<R: Rng>(model: &mut LinearModel, samples: &mut [Sample], rng: &mut R)
+{
+    for _ in 0..EPOCHS
+    {
+        samples.shuffle(rng);
+
+        for sample in samples.iter()
+        {
+            let error = sigmoid(model.logit(&sample.features)) - sample.outcome;
+
+            model.bias -= LEARNING_RATE * error;
+            for (weight, feature) in model.weights.iter_mut().zip(sample.features.iter())
+            {
+                *weight -= LEARNING_RATE * (error * feature + L2 * *weight);
+            }
+        }
+    }
+}
+
+/// Trains a model on `games` self-play games and reports how well it predicts
+/// the held-out games; the same `seed` plays the same games and fits the same model
+pub fn train(games: usize, seed: u64) -> LinearModel
+{
+    let held_out_games = ((games as f64 * HELD_OUT).ceil() as usize).max(1);
+
+    println!("Playing {} self-play games...", games);
+    let mut training = Vec::new();
+    let mut held_out = Vec::new();
+    for game in 0..games
+    {
+        let samples = self_play(&mut game_rng(seed, game));
+        if game < held_out_games { held_out.extend(samples) } else { training.extend(samples) }
+    }
+    println!("{} training positions, {} held out", training.len(), held_out.len());
+
+    let mut model = LinearModel::new();
+    fit(&mut model, &mut training, &mut StdRng::seed_from_u64(seed));
+
+    let (loss, accuracy) = assess(&model, &held_out);
+    println!("Held-out log loss {:.3} (0.693 for a coin flip), winner favoured in {:.1}% of decided positions", loss, accuracy * 100.0);
+
+    model
+}
+
+/// Elo difference that scores `score` of the points
+fn elo_difference(score: f64) -> f64
+{
+    let score = score.clamp(0.001, 0.999);
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Plays `model` against the classic heuristic, each pair seeded from `seed`, and times both searches
+pub fn benchmark(model: &LinearModel, pairs: usize, seed: u64)
+{
+    // The search takes evaluators that live as long as the program
+    let learned = Heuristic::Learned(Box::leak(Box::new(model.clone())));
+    let classic = con4_ai::classic();
+
+    let points: f64 = (0..pairs).map(|pair| connect4_pair(learned, classic, MATCH_DEPTH, &mut game_rng(seed, pair))).sum();
+    let score = points / (2 * pairs).max(1) as f64;
+    println!("Learned vs classic at depth {}: {:.1}% over {} games ({:+.0} Elo)", MATCH_DEPTH, score * 100.0, 2 * pairs, elo_difference(score));
+
+    for (name, heuristic) in [("classic", classic), ("learned", learned)]
+    {
+        let start = Instant::now();
+        con4_ai::score_moves(Connect4::initialize(), SPEED_DEPTH, heuristic);
+        println!("{} evaluation: depth {} from the empty board in {:.3}s", name, SPEED_DEPTH, start.elapsed().as_secs_f64());
+    }
+}
+
+/// Writes `model` where the client reads it from
+pub fn save(model: &LinearModel) -> std::io::Result<&'static str>
+{
+    let json = serde_json::to_string_pretty(model).expect("Error serializing the model");
+    fs::write(MODEL_FILE, json + "\n")?;
+    Ok(MODEL_FILE)
+}
@@ -0,0 +1,195 @@
+/*
+Self-play tuning of the heuristic weights: nudge one weight at a time and keep
+the change when the nudged weights beat the current ones over paired games
+*/
+use crate::con4_ai::Heuristic as Con4Heuristic;
+use crate::connect4::Connect4;
+use crate::eval_weights::EvalWeights;
+use crate::toot_and_otto::TootAndOtto;
+use crate::toot_ai::Heuristic;
+use crate::tournament::exact;
+use crate::{con4_ai, game_rng, toot_ai};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fs;
+
+/// Random plies played before each pair of games, so the pairs differ
+const OPENING_PLIES: usize = 2;
+
+/// Share of the points a candidate needs to replace the current weights
+const ACCEPT_SCORE: f64 = 0.55;
+
+/// Search depth of both sides during tuning
+const CONNECT4_DEPTH: usize = 4;
+const TOOT_DEPTH: usize = 3;
+
+/// Where the client reads its weights from
+const CONNECT4_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../game_core/src/connect4/con4_weights.json");
+const TOOT_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../game_core/src/toot_and_otto/toot_weights.json");
+
+/// The weights worth tuning with the range each stays in. The ranges keep every
+/// weight's sign, since crossing zero would flip what it rewards, and stop short
+/// of zero, where a weight would lose the size its steps are scaled from.
+/// A win, a spelled word and the decisive threats are left alone
+const CONNECT4_TUNED: [(&str, i32, i32); 5] = [
+    ("three", 1, 10000),
+    ("two", 1, 1000),
+    ("opponent_three", -10000, -1),
+    ("opponent_two", -1000, -1),
+    ("center", 1, 1000),
+];
+/// TOOT and OTTO tunes the inventory heuristic, the one its AI plays
+const TOOT_TUNED: [(&str, i32, i32); 4] = [
+    ("threat", 1, 50000),
+    ("later_threat", 1, 10000),
+    ("two_letters", 1, 1000),
+    ("one_letter", 1, 100),
+];
+
+fn weight<'a>(weights: &'a mut EvalWeights, name: &str) -> &'a mut i32
+{
+    match name
+    {
+        "three" => &mut weights.three,
+        "two" => &mut weights.two,
+        "opponent_three" => &mut weights.opponent_three,
+        "opponent_two" => &mut weights.opponent_two,
+        "center" => &mut weights.center,
+        "threat" => &mut weights.threat,
+        "later_threat" => &mut weights.later_threat,
+        "two_letters" => &mut weights.two_letters,
+        "one_letter" => &mut weights.one_letter,
+        _ => unreachable!("not a tuned weight"),
+    }
+}
+
+/// Copy of `weights` with one weight scaled by up to half its size, kept in its range
+fn perturb<R: Rng>(weights: &EvalWeights, tuned: &[(&str, i32, i32)], rng: &mut R) -> (EvalWeights, String)
+{
+    let mut candidate = *weights;
+    let (name, min, max) = tuned[rng.gen_range(0, tuned.len())];
+    let value = weight(&mut candidate, name);
+
+    let step = ((value.abs() as f64 * rng.gen_range(0.1, 0.5)).round() as i32).max(1);
+    let old = *value;
+    let new = if rng.gen() { old + step } else { old - step };
+    *value = new.clamp(min, max);
+
+    let change = format!("{} {} -> {}", name, old, *value);
+    (candidate, change)
+}
+
+/// Plays a random opening twice with the sides swapped at `depth`, returning the candidate's points
+pub fn connect4_pair<R: Rng>(candidate: Con4Heuristic, current: Con4Heuristic, depth: usize, rng: &mut R) -> f64
+{
+    let mut opening = Connect4::initialize();
+    for _ in 0..OPENING_PLIES
+    {
+        match con4_ai::random_move(opening, rng)
+        {
+            Some(col) => opening.place(col),
+            None => break,
+        };
+    }
+
+    let mut points = 0.0;
+    for candidate_side in [opening.current_player, opening.current_player.switch()]
+    {
+        let mut board = opening;
+        while !board.termination
+        {
+            let heuristic = if board.current_player == candidate_side { candidate } else { current };
+            match con4_ai::ai_next_move(board, exact(depth), heuristic, rng)
+            {
+                Some(col) => board.place(col),
+                None => break,
+            };
+        }
+
+        points += match board.winner
+        {
+            Some(winner) if winner == candidate_side => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        };
+    }
+
+    points
+}
+
+fn toot_pair<R: Rng>(candidate: &EvalWeights, current: &EvalWeights, rng: &mut R) -> f64
+{
+    let mut opening = TootAndOtto::new();
+    for _ in 0..OPENING_PLIES
+    {
+        match toot_ai::random_move(opening, rng)
+        {
+            Some((col, letter)) => opening.drop(letter, col),
+            None => break,
+        };
+    }
+
+    let mut points = 0.0;
+    for candidate_side in [opening.current_player, opening.current_player.switch()]
+    {
+        let mut board = opening;
+        while !board.termination
+        {
+            let weights = if board.current_player == candidate_side { candidate } else { current };
+            let scores = toot_ai::score_moves(board, TOOT_DEPTH, Heuristic::Inventory(*weights), rng);
+            let values: Vec<i32> = scores.iter().map(|(_, _, value)| *value).collect();
+            match exact(TOOT_DEPTH).choose(&values, rng)
+            {
+                Some(index) => board.drop(scores[index].1, scores[index].0),
+                None => break,
+            };
+        }
+
+        points += match board.winner
+        {
+            Some(winner) if winner == candidate_side => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        };
+    }
+
+    points
+}
+
+/// Hill-climbs the shipped weights of one game for `rounds` rounds of `pairs`
+/// game pairs each, returning the best weights found. The same `seed` makes the
+/// same changes and plays every pair the same
+pub fn tune(toot: bool, rounds: usize, pairs: usize, seed: u64) -> EvalWeights
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut current = if toot { toot_ai::weights() } else { con4_ai::weights() };
+    let tuned: &[(&str, i32, i32)] = if toot { &TOOT_TUNED } else { &CONNECT4_TUNED };
+
+    for round in 1..=rounds
+    {
+        let (candidate, change) = perturb(&current, tuned, &mut rng);
+
+        let points: f64 = (0..pairs).map(|pair| {
+            let mut rng = game_rng(seed, round * pairs + pair);
+            if toot { toot_pair(&candidate, &current, &mut rng) } else { connect4_pair(Con4Heuristic::Classic(candidate), Con4Heuristic::Classic(current), CONNECT4_DEPTH, &mut rng) }
+        }).sum();
+        let score = points / (2 * pairs) as f64;
+
+        let accepted = score >= ACCEPT_SCORE;
+        if accepted
+        {
+            current = candidate;
+        }
+        println!("Round {:>3}: {:<32} scored {:>5.1}% {}", round, change, score * 100.0, if accepted { "kept" } else { "" });
+    }
+
+    current
+}
+
+/// Writes `weights` where the client and the AIs of `game_test` load them from
+pub fn save(weights: &EvalWeights, toot: bool) -> std::io::Result<&'static str>
+{
+    let path = if toot { TOOT_FILE } else { CONNECT4_FILE };
+    fs::write(path, weights.to_json() + "\n")?;
+    Ok(path)
+}
@@ -1,7 +1,7 @@
 use crate::{
 	components::{
-		connect4_page::Connect4Page, auth::AuthPage, sidebar::SideBar, stats::Stats,
-		toot_and_otto_page::TootAndOttoPage,
+		connect4_page::Connect4Page, auth::AuthPage, history::History, sidebar::SideBar, stats::Stats,
+		replay::Replay, toot_and_otto_page::TootAndOttoPage,
 	},
 	switch::{AppRoute, AppRouter, PublicUrlSwitch},
 };
@@ -30,9 +30,13 @@ impl Component for App {
 	fn view(&self) -> Html {
 		let get_route = AppRouter::render(|switch: PublicUrlSwitch| match switch.route() {
 			AppRoute::Connect4 => html! {<Connect4Page />},
+			AppRoute::Connect4Room(code) => html! {<Connect4Page room=Some(code) />},
 			AppRoute::Login => html! {<AuthPage/>},
 			AppRoute::Stats => html! {<Stats />},
+			AppRoute::History => html! {<History />},
 			AppRoute::TootAndOtto => html! {<TootAndOttoPage />},
+			AppRoute::TootAndOttoRoom(code) => html! {<TootAndOttoPage room=Some(code) />},
+			AppRoute::Replay(id) => html! {<Replay id=id />},
 			AppRoute::Home => html! {<AuthPage/>},
 		});
 
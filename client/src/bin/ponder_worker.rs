@@ -0,0 +1,10 @@
+// The Connect 4 page's pondering worker, which Trunk builds as a script of its own
+#[path = "../ponder.rs"]
+mod ponder;
+
+use yew::agent::Threaded;
+
+fn main() {
+	wasm_logger::init(wasm_logger::Config::default());
+	ponder::Ponderer::register();
+}
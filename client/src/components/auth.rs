@@ -1,8 +1,10 @@
 // Login, Reg, & Logout
 use serde_json::json;
-use yew::format::Json;
+use yew::format::{Json, Nothing};
 use yew::prelude::*;
 use yew::services::fetch::{FetchService, FetchTask, Request, Response};
+use serde::{Deserialize, Serialize};
+use crate::types::session;
 
 // Struct for Auth Page
 pub struct AuthPage {
@@ -19,7 +21,15 @@ pub enum Message {
     Logout,
     UpdateUsername(String),
     UpdatePassword(String),
-    ReceiveResponse(Result<String, anyhow::Error>),
+    ReceiveResponse(Result<SessionResponse, anyhow::Error>),
+    LoggedOut,
+}
+
+// Server reply to a login or sign up: a status and the session token
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionResponse {
+    pub status: String,
+    pub token: Option<String>,
 }
 
 impl AuthPage {
@@ -39,7 +49,7 @@ impl AuthPage {
         // Creat a callback that will receive a turple of data:
         let callback = self
             .link
-            .callback(|response: Response<Json<Result<String, anyhow::Error>>>| {
+            .callback(|response: Response<Json<Result<SessionResponse, anyhow::Error>>>| {
                 let Json(data) = response.into_body();
                 Message::ReceiveResponse(data)
             });
@@ -48,6 +58,21 @@ impl AuthPage {
         // Store the task prevent losting immediately
         self.fetch_task = Some(task);
     }
+
+    // Ask the server to end the session, so the token stops working
+    pub fn logout(&mut self) {
+        let authorization = match session::authorization() {
+            Some(authorization) => authorization,
+            None => return,
+        };
+        let request = Request::post("http://localhost:8000/logout")
+            .header("Authorization", authorization)
+            .body(Nothing)
+            .expect("Build Request Failed");
+        let callback = self.link.callback(|_: Response<Nothing>| Message::LoggedOut);
+        let task = FetchService::fetch(request, callback).expect("failed to start request");
+        self.fetch_task = Some(task);
+    }
 }
 
 impl Component for AuthPage {
@@ -90,12 +115,14 @@ impl Component for AuthPage {
             Message::ReceiveResponse(response) => {
                 // Parse response from server
                 let window = web_sys::window().unwrap();
-                let loc_storage = window.local_storage().unwrap().unwrap();
-                match response.unwrap().as_str() {
-                    "Login OK" => {
-                        // Add logged in user to local storage
-                        loc_storage.set_item("LoggedIn", &self.username)
-                            .expect("Error setting user login");
+                let response = match response {
+                    Ok(response) => response,
+                    Err(_) => SessionResponse { status: String::new(), token: None },
+                };
+                match (response.status.as_str(), &response.token) {
+                    ("Login OK", Some(token)) => {
+                        // Add logged in user and their session to local storage
+                        session::save(&self.username, token);
 
                         // Navigate to connect 4 page
                         let document = window.document().unwrap();
@@ -104,10 +131,9 @@ impl Component for AuthPage {
                                           location.host().expect("error"), "connect-4/");
                         location.set_href(&url).expect("failed");
                     }
-                    "Created user" => {
-                        // Add logged in user to local storage
-                        loc_storage.set_item("LoggedIn", &self.username)
-                            .expect("Error setting user login");
+                    ("Created user", Some(token)) => {
+                        // Add logged in user and their session to local storage
+                        session::save(&self.username, token);
 
                         // Navigate to connect 4 page
                         let document = window.document().unwrap();
@@ -120,21 +146,20 @@ impl Component for AuthPage {
                         );
                         location.set_href(&url).expect("failed");
                     }
-                    "Login Failed" => self.error = Some(String::from("Login Failed")),
-                    "Username Taken" => self.error = Some(String::from("This username is already taken.")),
+                    ("Login Failed", _) => self.error = Some(String::from("Login Failed")),
+                    ("Username Taken", _) => self.error = Some(String::from("This username is already taken.")),
                     _ => {
                         // Clear user login credential input:
-                        loc_storage.set_item("LoggedIn", &"").expect("Clear Failed");
+                        session::clear();
                     }
                 }
             }
             Message::Logout => {
-                // Logout and clear local storage
-                let window = web_sys::window().unwrap();
-                let loc_storage = window.local_storage().unwrap().unwrap();
-                loc_storage.set_item("LoggedIn", &"")
-                    .expect("Clear Failed");
+                // End the session on the server and clear local storage
+                self.logout();
+                session::clear();
             }
+            Message::LoggedOut => {}
         }
         true
     }
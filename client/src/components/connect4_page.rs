@@ -1,19 +1,81 @@
 use crate::{
 	connect4::{
 		connect4::{Connect4, NUM_COLS, NUM_ROWS, C4Piece, C4Piece::*},
-		con4_ai,
+		con4_ai::{self, Heuristic},
+	},
+	ponder::{PonderRequest, Pondered, Ponderer},
+	types::{
+		adaptive::AdaptiveLevel,
+		analysis::{advantage, Limit},
+		online::{Online, OnlineEvent},
+		opponent::{Opponent, DEFAULT_LEVEL},
+		review::Review,
+		session,
+		strength::{level_elo, MAX_LEVEL},
 	},
-	types::opponent::Opponent,
 };
+use game_core::online::ServerMessage;
+use game_core::record::{self, Evaluator, StartedGame};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use serde_json::json;
-use strum::IntoEnumIterator;
 use yew::format::Json;
+use yew::agent::{Bridge, Bridged};
 use yew::services::fetch::{FetchService, FetchTask, Request, Response};
-use yew::{html, Component, ComponentLink, Html, ShouldRender};
+use yew::{html, Component, ComponentLink, Html, InputData, Properties, ShouldRender};
+
+/// Local storage name of this game's adaptive level
+const GAME: &str = "connect4";
+
+/// How long the hint and the evaluation bar search
+const ANALYSIS_TIME: Limit = Limit::Millis(500);
+
+/// How deep the post-game review searches each position
+const REVIEW_DEPTH: Limit = Limit::Depth(4);
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+	/// Code of the private room to join, from a `/connect-4/room/<code>` link
+	#[prop_or_default]
+	pub room: Option<String>,
+}
 
 pub struct Connect4Page {
 	board: Connect4,
 	opponent: Opponent,
+	adaptive: AdaptiveLevel,
+	/// Which evaluation the computer plays with, picked before the first move
+	evaluator: Evaluator,
+	/// Leaf evaluation of every search on this page, `evaluator`'s heuristic
+	heuristic: Heuristic,
+	/// Column the last hint recommended, cleared once a piece is placed
+	hint: Option<usize>,
+	/// Whether a hint was asked for during this game
+	hinted: bool,
+	/// P1's share of the evaluation bar, `None` while the bar is hidden
+	evaluation: Option<f64>,
+	/// Columns played this game, in order
+	moves: Vec<usize>,
+	/// Judged moves once a finished game is being reviewed
+	review: Option<Review<usize>>,
+	/// How many moves of the reviewed game are on the board
+	review_step: usize,
+	/// When the first move was played, in milliseconds since the Unix epoch
+	started_at: f64,
+	/// Seed of this game's AI choices; entering it before the first move replays the game
+	seed: u64,
+	rng: StdRng,
+	/// Id of the game the server started with `seed`, without which the game does not score
+	game_id: Option<String>,
+	start_task: Option<FetchTask>,
+	/// Worker searching the AI's replies while the human picks a column
+	ponderer: Box<dyn Bridge<Ponderer>>,
+	/// Replies it searched that this game may still reach
+	pondered: Vec<Pondered>,
+	/// The live game while the opponent is `Opponent::Online`
+	online: Option<Online>,
+	/// The room the page was opened to join
+	room: Option<String>,
 	link: ComponentLink<Self>,
 	fetch_task: Option<FetchTask>,
 }
@@ -22,25 +84,206 @@ pub enum Msg {
 	MakeMove(usize),
 	Reset,
 	ChangeOpponent(Opponent),
+	Hint,
+	ToggleEvaluation,
+	Review,
+	ReviewStep(usize),
+	ChangeSeed(u64),
+	ChangeEvaluator(Evaluator),
+	GameStarted(Option<StartedGame>),
+	Pondered(Pondered),
+	Online(OnlineEvent),
+	Resign,
 	ReceiveResponse(Result<String, anyhow::Error>),
 }
 
+/// The piece in one cell of `board`, highlighted when it is part of the winning line
+pub fn view_piece(board: &Connect4, row: usize, col: usize) -> Html {
+	let mut classes = String::from("piece");
+
+	if let Some(_) = board.winner {
+		if board
+			.check_win(board.current_player.switch())
+			.unwrap()
+			.contains(&[row, col])
+		{
+			classes.push_str(" piece--winner");
+		}
+	}
+
+	classes.push_str(match board.board[row][col] {
+		None => " piece--empty",
+		Some(color) => match color {
+			P1 => " piece--p1",
+			P2 => " piece--p2",
+		},
+	});
+	html! {<div class=classes></div>}
+}
+
+/// Border of the board, in the colour of the player to move
+pub fn board_class(board: &Connect4) -> &'static str {
+	match board.current_player {
+		P1 => "board--p1",
+		P2 => "board--p2",
+	}
+}
+
+/// Seat of the player to move, as live games count them
+fn seat_to_move(board: &Connect4) -> usize {
+	(board.current_player == P2) as usize
+}
+
 impl Connect4Page {
-	fn update_score(&mut self, result: u8, human_flag: u8) {
-		let ls = web_sys::window().unwrap().local_storage().unwrap().unwrap();
-		let username = match ls.get_item("LoggedIn") {
-			Ok(a) => match a {
-				Some(b) => b,
-				None => "".to_string(),
-			},
-			Err(_) => "".to_string(),
+	/// Records a finished game: sends the score and moves the adaptive level
+	fn finish_game(&mut self, result: u8) {
+		// The server replays the game at the level it was played at, so send it first
+		self.update_score(result);
+		if self.opponent == Opponent::Adaptive {
+			// `result` is from the first player's side, which is always the human
+			let points = match result {
+				1 => 1.0,
+				0 => 0.0,
+				_ => 0.5,
+			};
+			self.adaptive.record(GAME, points);
+		}
+		self.refresh_analysis();
+		self.start_pondering();
+	}
+
+	/// The board after the first `plies` moves of this game
+	fn replay(&self, plies: usize) -> Connect4 {
+		let mut board = Connect4::initialize();
+		for col in self.moves[..plies].iter() {
+			board.place(*col);
+		}
+		board
+	}
+
+	/// Drops a stale hint and re-evaluates the position if the bar is shown
+	fn refresh_analysis(&mut self) {
+		self.hint = None;
+		if self.evaluation.is_some() {
+			self.evaluation = Some(self.evaluate());
+		}
+	}
+
+	/// P1's share of the evaluation bar for the current position
+	fn evaluate(&self) -> f64 {
+		if self.board.termination {
+			return match self.board.winner {
+				Some(P1) => 1.0,
+				Some(P2) => 0.0,
+				None => 0.5,
+			};
+		}
+
+		let share = match con4_ai::analyze(self.board, ANALYSIS_TIME, self.heuristic).moves.first() {
+			Some(best) => advantage(best, con4_ai::EVALUATION_SCALE),
+			None => 0.5,
 		};
-		if username == "" {
-			return;
+		match self.board.current_player {
+			P1 => share,
+			P2 => 1.0 - share,
 		}
-		let body = &json!({"username": &username, "mode": 0, "result": result, "human_flag": human_flag});
+	}
+
+	/// Has the worker ponder the AI's reply to every column the human may play,
+	/// the predicted one first. Each is the same search to the same depth the AI
+	/// runs when the column is played, only done earlier, so the game the server
+	/// replays is unchanged
+	fn start_pondering(&mut self) {
+		let moves = &self.moves;
+		self.pondered.retain(|pondered| pondered.moves.starts_with(moves));
+		let strength = match self.opponent.strength(&self.adaptive) {
+			Some(strength) if !self.board.termination && self.review.is_none() => strength,
+			_ => {
+				self.ponderer.send(PonderRequest::Stop);
+				return;
+			}
+		};
+		self.ponderer.send(PonderRequest::Ponder { moves: self.moves.clone(), depth: strength.depth, evaluator: self.evaluator });
+	}
+
+	/// Takes the pondered scores of the AI's reply to the human's last column, if the worker has them
+	fn take_pondered(&mut self, depth: usize) -> Option<Vec<(usize, i32)>> {
+		let (col, before) = self.moves.split_last()?;
+		let evaluator = self.evaluator;
+		let index = self.pondered.iter().position(|pondered| {
+			pondered.col == *col && pondered.moves == before && pondered.depth == depth && pondered.evaluator == evaluator
+		})?;
+		Some(self.pondered.swap_remove(index).scores)
+	}
+
+	/// Connects to the server; the player joins the queue from there
+	fn connect_online(&mut self) {
+		self.online = Some(Online::connect(record::CONNECT4, self.link.callback(Msg::Online)));
+	}
+
+	/// Plays online in the room a friend shared the code of
+	fn join_room(&mut self, code: String) {
+		if let Some(online) = self.online.as_mut() {
+			online.leave();
+		}
+		self.opponent = Opponent::Online;
+		self.evaluation = None;
+		self.online = Some(Online::join(record::CONNECT4, code, self.link.callback(Msg::Online)));
+	}
+
+	/// Asks the server to start the next game against the computer. Its seed replaces
+	/// the page's own, and only a game the server started scores
+	fn start_game(&mut self) {
+		self.game_id = None;
+		self.start_task = None;
+		let ai = match self.opponent.ai(&self.adaptive) {
+			Some(ai) => ai,
+			None => return,
+		};
+		let authorization = match session::authorization() {
+			Some(authorization) => authorization,
+			None => return,
+		};
+		let body = &json!({
+			"mode": record::CONNECT4,
+			"ai": ai,
+			"evaluator": self.evaluator,
+		});
+		let request = Request::post("http://localhost:8000/start_game")
+			.header("Content-Type", "application/json")
+			.header("Authorization", authorization)
+			.body(Json(body))
+			.expect("Build Request Failed");
+		let callback = self.link.callback(|response: Response<Json<Result<Option<StartedGame>, anyhow::Error>>>| {
+			let Json(data) = response.into_body();
+			Msg::GameStarted(data.ok().flatten())
+		});
+		self.start_task = FetchService::fetch(request, callback).ok();
+	}
+
+	fn update_score(&mut self, result: u8) {
+		// Scores go to whoever the session belongs to; without one nothing is recorded
+		let authorization = match session::authorization() {
+			Some(authorization) => authorization,
+			None => return,
+		};
+		// The whole game goes along, so the server can replay it and check the result
+		let body = &json!({
+			"mode": record::CONNECT4,
+			"moves": self.moves.iter().map(|col| record::connect4_notation(*col)).collect::<Vec<_>>(),
+			"ai": self.opponent.ai(&self.adaptive),
+			"seed": self.seed,
+			"evaluator": self.evaluator,
+			"game_id": self.game_id,
+			"result": result,
+			"hinted": self.hinted as u8,
+			"variant": self.opponent.variant(),
+			"started_at": self.started_at as i64,
+			"client_version": env!("CARGO_PKG_VERSION"),
+		});
 		let request = Request::post("http://localhost:8000/update_score")
 			.header("Content-Type", "application/json")
+			.header("Authorization", authorization)
 			.body(Json(body))
 			.expect("Build Request Failed");
 
@@ -59,30 +302,57 @@ impl Connect4Page {
 
 impl Component for Connect4Page {
 	type Message = Msg;
-	type Properties = ();
-	fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
-		Self {
+	type Properties = Props;
+	fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+		let seed = rand::random();
+		let ponderer = Ponderer::bridge(link.callback(Msg::Pondered));
+		let mut page = Self {
 			link,
 			board: Connect4::initialize(),
-			opponent: Opponent::ExpertMode,
+			opponent: Opponent::Computer(DEFAULT_LEVEL),
+			adaptive: AdaptiveLevel::load(GAME),
+			evaluator: Evaluator::Classic,
+			heuristic: con4_ai::classic(),
+			hint: None,
+			hinted: false,
+			evaluation: None,
+			moves: Vec::new(),
+			started_at: 0.0,
+			review: None,
+			review_step: 0,
+			seed,
+			rng: StdRng::seed_from_u64(seed),
+			game_id: None,
+			start_task: None,
+			ponderer,
+			pondered: Vec::new(),
+			online: None,
+			room: props.room.clone(),
 			fetch_task: None,
+		};
+		page.start_game();
+		page.start_pondering();
+		if let Some(code) = props.room {
+			page.join_room(code);
 		}
+		page
 	}
 
 	fn update(&mut self, msg: Self::Message) -> ShouldRender {
-		let mut human_flag: u8 = 0; 
 		match msg {
 			Msg::MakeMove(col) => {
-				let difficulty = match self.opponent {
-					Opponent::Human => {
-						human_flag = 1;
-						0
-					},
-					Opponent::EasyMode => 1,
-					Opponent::NormalMode => 2,
-					Opponent::ExpertMode => 3,
-				};
-				if let Some(_) = self.board.winner {
+				// Online the server has the board: the move goes there and is placed once it comes back
+				if let Some(online) = self.online.as_mut() {
+					let mut board = self.board;
+					if online.my_turn(seat_to_move(&self.board)) && board.place(col) {
+						online.play(record::connect4_notation(col));
+					}
+					return false;
+				}
+				let strength = self.opponent.strength(&self.adaptive);
+				if self.review.is_some() {
+					return false;
+				} else if let Some(_) = self.board.winner {
 					return false;
 				} else if self.board.next_step == 42 {
 					return false;
@@ -91,41 +361,183 @@ impl Component for Connect4Page {
 				if self.board.place(col) == false {
 					return false;
 				}
+				if self.moves.is_empty() {
+					self.started_at = js_sys::Date::now();
+				}
+				self.moves.push(col);
 				if let Some(winner) = self.board.winner {
 					match winner {
-						C4Piece::P1 => self.update_score(1, human_flag),
-						C4Piece::P2 => self.update_score(0, human_flag),
+						C4Piece::P1 => self.finish_game(1),
+						C4Piece::P2 => self.finish_game(0),
 					}
 					return true;
 				} else if self.board.winner.is_none() && self.board.termination {
-					self.update_score(2, human_flag);
+					self.finish_game(2);
 					return true;
 				}
-				if difficulty != 0 {
-					self.board.place(con4_ai::AI_next_move(self.board, difficulty));
+				if let Some(strength) = strength {
+					let board = self.board;
+					let heuristic = self.heuristic;
+					let pondered = self.take_pondered(strength.depth);
+					let ai_col = con4_ai::reply(board, strength, &mut self.rng, || {
+						pondered.unwrap_or_else(|| con4_ai::score_moves(board, strength.depth, heuristic))
+					});
+					if let Some(ai_col) = ai_col {
+						self.board.place(ai_col);
+						self.moves.push(ai_col);
+					}
 				}
 
 				if let Some(winner) = self.board.winner {
 					match winner {
 
-						C4Piece::P1 => self.update_score(1, human_flag),
-						C4Piece::P2 => self.update_score(0, human_flag),
+						C4Piece::P1 => self.finish_game(1),
+						C4Piece::P2 => self.finish_game(0),
 
 					}
 					return true;
 				} else if self.board.winner.is_none() && self.board.termination {
-					self.update_score(2, human_flag);
+					self.finish_game(2);
 					return true;
 				}
+				self.refresh_analysis();
+				self.start_pondering();
 			}
 			Msg::Reset => {
+				// An online game in progress can only be resigned
+				if self.online.as_ref().is_some_and(|online| online.playing()) {
+					return false;
+				}
 				self.board = Connect4::initialize();
+				self.seed = rand::random();
+				self.rng = StdRng::seed_from_u64(self.seed);
+				self.hinted = false;
+				self.moves.clear();
+				self.pondered.clear();
+				self.review = None;
+				self.refresh_analysis();
+				self.start_game();
+				self.start_pondering();
+				if let Some(online) = self.online.as_mut() {
+					online.reset();
+				}
+			}
+			Msg::Hint => {
+				if self.board.termination || self.review.is_some() || self.online.is_some() {
+					return false;
+				}
+				self.hint = con4_ai::analyze(self.board, ANALYSIS_TIME, self.heuristic).moves.first().map(|best| best.mv);
+				self.hinted = true;
+			}
+			Msg::ToggleEvaluation => {
+				if self.online.is_some() && !self.board.termination {
+					return false;
+				}
+				self.evaluation = match self.evaluation {
+					Some(_) => None,
+					None => Some(self.evaluate()),
+				};
+			}
+			Msg::Review => {
+				if !self.board.termination || self.review.is_some() {
+					return false;
+				}
+				self.review = Some(con4_ai::review(&self.moves, REVIEW_DEPTH, self.heuristic));
+				self.start_pondering();
+				self.review_step = self.moves.len();
+			}
+			Msg::ReviewStep(step) => {
+				let step = step.min(self.moves.len());
+				self.review_step = step;
+				self.board = self.replay(step);
+				self.refresh_analysis();
+				// Show what the engine would have played next
+				self.hint = self.review.as_ref().and_then(|review| review.moves.get(step)).map(|judged| judged.best);
+			}
+			Msg::ChangeSeed(seed) => {
+				if self.board.next_step == 0 && self.review.is_none() {
+					self.seed = seed;
+					self.rng = StdRng::seed_from_u64(seed);
+					// A replayed seed is the player's own, so the game no longer scores
+					self.game_id = None;
+					self.start_task = None;
+				}
+			}
+			Msg::ChangeEvaluator(evaluator) => {
+				if self.board.next_step == 0 && self.review.is_none() {
+					self.evaluator = evaluator;
+					self.heuristic = evaluator.heuristic();
+					self.refresh_analysis();
+					self.start_game();
+					self.start_pondering();
+				}
+			}
+			Msg::GameStarted(started) => {
+				// Too late once the game is under way; it plays on with the page's seed
+				let started = match started {
+					Some(started) if self.moves.is_empty() && self.review.is_none() => started,
+					_ => return false,
+				};
+				self.seed = started.seed;
+				self.rng = StdRng::seed_from_u64(started.seed);
+				self.game_id = Some(started.id);
 			}
 			Msg::ChangeOpponent(opponent) => {
-				if self.board.next_step == 0 {
+				if self.board.next_step == 0 && self.review.is_none() {
+					if let Some(online) = self.online.as_mut() {
+						online.leave();
+					}
+					self.online = None;
 					self.opponent = opponent;
+					if opponent == Opponent::Online {
+						self.evaluation = None;
+						self.connect_online();
+					}
+					self.start_game();
+					self.start_pondering();
 				}
 			}
+			Msg::Pondered(pondered) => {
+				// Replies to a position the game has left behind are of no more use
+				if pondered.moves.starts_with(&self.moves) {
+					self.pondered.push(pondered);
+				}
+				return false;
+			}
+			Msg::Online(event) => {
+				let message = match self.online.as_mut() {
+					Some(online) => online.handle(event),
+					None => return false,
+				};
+				match message {
+					// A new game, or the one the page reconnected to: set up the moves so far
+					Some(ServerMessage::Game(state)) => {
+						self.board = Connect4::initialize();
+						self.moves.clear();
+						self.review = None;
+						self.started_at = js_sys::Date::now();
+						for col in state.moves.iter().filter_map(|mv| record::parse_connect4(mv)) {
+							self.board.place(col);
+							self.moves.push(col);
+						}
+					}
+					Some(ServerMessage::Moved { mv, .. }) => {
+						if let Some(col) = record::parse_connect4(&mv) {
+							if self.board.place(col) {
+								self.moves.push(col);
+							}
+						}
+					}
+					_ => {}
+				}
+				self.refresh_analysis();
+			}
+			Msg::Resign => {
+				if let Some(online) = self.online.as_mut() {
+					online.resign();
+				}
+				return false;
+			}
 			Msg::ReceiveResponse(response) => match response.unwrap().as_str() {
 				"Update success" => {}
 				_ => {}
@@ -135,36 +547,18 @@ impl Component for Connect4Page {
 		true
 	}
 
-	fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+	fn change(&mut self, props: Self::Properties) -> ShouldRender {
+		if props.room != self.room {
+			self.room = props.room.clone();
+			if let Some(code) = props.room {
+				self.join_room(code);
+			}
+		}
 		true
 	}
 
 	fn view(&self) -> Html {
 
-		let check_piece = move |row: usize, col: usize| -> Html {
-			let mut classes = String::from("piece");
-
-			if let Some(_) = self.board.winner {
-				if self
-					.board
-					.check_win(self.board.current_player.switch())
-					.unwrap()
-					.contains(&[row, col])
-				{
-					classes.push_str(" piece--winner");
-				}
-			}
-
-			classes.push_str(match self.board.board[row][col] {
-				None => " piece--empty",
-				Some(color) => match color {
-					P1 => " piece--p1",
-					P2 => " piece--p2",
-				},
-			});
-			html! {<div class=classes></div>}
-		};
-
 		let game_status = move || -> Html {
 
 			let status_text = match (self.board.termination,self.board.current_player, self.opponent, self.board.winner) {
@@ -179,6 +573,11 @@ impl Component for Connect4Page {
 				(true, _,_, Some(P2)) => "COMPUTER WINS",
 				(true,_,_,None) => "TIE GAME",
 			};
+			// Online the players go by their names
+			let status_text = match &self.online {
+				Some(online) => online.status_text(seat_to_move(&self.board)),
+				None => status_text.to_string(),
+			};
 
 			let text_color_class = move || -> &str {
 				match (self.board.termination,self.board.current_player, self.board.winner) {
@@ -201,7 +600,13 @@ impl Component for Connect4Page {
 			html! {
 				<div class=format!("opponent {}", if self.board.next_step > 0 { "opponent--disabled" } else { "" }) >
 					{
-						Opponent::iter().map(|opponent| {
+						// Connect 4 has not been solved, so there is no Expert+ level
+						Opponent::choices().iter().filter(|opponent| **opponent != Opponent::PerfectMode).map(|opponent| {
+							// Keep the chosen level when the computer button is pressed again
+							let opponent = match (*opponent, self.opponent) {
+								(Opponent::Computer(_), Opponent::Computer(level)) => Opponent::Computer(level),
+								(opponent, _) => opponent,
+							};
 							html! {
 								<button
 									class=format!("opponent__button {}", if self.opponent == opponent {"opponent__button--selected"} else {""})
@@ -215,17 +620,145 @@ impl Component for Connect4Page {
 			}
 		};
 
-		let hover_col_class = move || -> &str {
-			match self.board.current_player {
+		let level_picker = move || -> Html {
+			match self.opponent {
+				Opponent::Computer(level) => html! {
+					<div class="level">
+						<input
+							type="range"
+							class="level__slider"
+							min="1"
+							max=MAX_LEVEL.to_string()
+							value=level.to_string()
+							disabled={self.board.next_step > 0}
+							oninput=self.link.callback(|e: InputData| Msg::ChangeOpponent(Opponent::Computer(e.value.parse().unwrap_or(DEFAULT_LEVEL))))
+						/>
+						<span class="level__label">{format!("Level {} (~{} Elo)", level, con4_ai::LEVEL_ELO[level as usize - 1])}</span>
+					</div>
+				},
+				Opponent::Adaptive => html! {
+					<div class="level">
+						<span class="level__label">{format!(
+							"Adaptive level {:.1} (~{} Elo), you scored {:.0}% of your last {} games",
+							self.adaptive.level,
+							level_elo(&con4_ai::LEVEL_ELO, self.adaptive.level),
+							self.adaptive.win_rate() * 100.0,
+							self.adaptive.results.len(),
+						)}</span>
+					</div>
+				},
+				Opponent::Online => match &self.online {
+					Some(online) => online.view(),
+					None => html! {},
+				},
+				_ => html! {},
+			}
+		};
+
+		let seed_picker = move || -> Html {
+			html! {
+				<div class="seed">
+					<span class="seed__label">{"Seed"}</span>
+					<input
+						type="text"
+						class="seed__input"
+						inputmode="numeric"
+						value=self.seed.to_string()
+						disabled={self.board.next_step > 0}
+						oninput=self.link.callback(|e: InputData| Msg::ChangeSeed(e.value.parse().unwrap_or(0)))
+					/>
+				</div>
+			}
+		};
+
+		// The classic weights or the learned model, for the computer, hints and the evaluation bar
+		let evaluator_picker = move || -> Html {
+			if self.opponent.strength(&self.adaptive).is_none() {
+				return html! {};
+			}
+			let next = match self.evaluator {
+				Evaluator::Classic => Evaluator::Learned,
+				Evaluator::Learned => Evaluator::Classic,
+			};
+
+			html! {
+				<label class="evaluator">
+					<input
+						type="checkbox"
+						class="evaluator__checkbox"
+						checked={self.evaluator == Evaluator::Learned}
+						disabled={self.board.next_step > 0}
+						onclick=self.link.callback(move |_| Msg::ChangeEvaluator(next))
+					/>
+					<span class="evaluator__label">{"Learned evaluation"}</span>
+				</label>
+			}
+		};
+
+		let hover_col_class = move |col: usize| -> String {
+			let hover = match self.board.current_player {
 				P1 => "column--p1",
 				P2 => "column--p2",
+			};
+			if self.hint == Some(col) {
+				format!("{} column--hint", hover)
+			} else {
+				hover.to_string()
 			}
 		};
 
-		let board_border_class = move || -> &str {
-			match self.board.current_player {
-				P1 => "board--p1",
-				P2 => "board--p2",
+		let review_panel = move || -> Html {
+			let review = match &self.review {
+				Some(review) => review,
+				None => return html! {},
+			};
+			let step = self.review_step;
+			let last = review.moves.len();
+
+			html! {
+				<div class="review">
+					<div class="review__controls">
+						<button onclick=self.link.callback(|_| Msg::ReviewStep(0))>{"<<"}</button>
+						<button onclick=self.link.callback(move |_| Msg::ReviewStep(step.saturating_sub(1)))>{"<"}</button>
+						<span class="review__position">{format!("Move {} / {}", step, last)}</span>
+						<button onclick=self.link.callback(move |_| Msg::ReviewStep(step + 1))>{">"}</button>
+						<button onclick=self.link.callback(move |_| Msg::ReviewStep(last))>{">>"}</button>
+					</div>
+					<ol class="review__moves">
+					{
+						review.moves.iter().enumerate().map(|(index, judged)| {
+							let mut classes = format!("review__move review__move--{}", judged.verdict.to_string().to_lowercase());
+							if index + 1 == step {
+								classes.push_str(" review__move--current");
+							}
+							let turning_point = review.turning_point == Some(index);
+							if turning_point {
+								classes.push_str(" review__move--turning-point");
+							}
+
+							html! {
+								<li class=classes onclick=self.link.callback(move |_| Msg::ReviewStep(index + 1))>
+									{format!("{} column {}", if index % 2 == 0 { "P1" } else { "P2" }, judged.mv + 1)}
+									<span class="review__verdict">{judged.verdict}</span>
+									{if judged.best != judged.mv { format!("best was column {}", judged.best + 1) } else { String::new() }}
+									{if turning_point { html! {<span class="review__turning-point">{"Turning point"}</span>} } else { html! {} }}
+								</li>
+							}
+						}).collect::<Html>()
+					}
+					</ol>
+				</div>
+			}
+		};
+
+		let evaluation_bar = move || -> Html {
+			match self.evaluation {
+				Some(share) => html! {
+					<div class="evaluation">
+						<div class="evaluation__p1" style=format!("width: {:.0}%", share * 100.0)></div>
+					</div>
+				},
+				None => html! {},
 			}
 		};
 
@@ -233,19 +766,33 @@ impl Component for Connect4Page {
 		html! {
 			<div class="container">
 			<div class="selection">
-				<button class="selection__reset" onclick=self.link.callback(move |_| Msg::Reset)>{"RESET"}</button>
+				{
+					if self.online.as_ref().is_some_and(|online| online.playing()) {
+						html! {<button class="selection__reset" onclick=self.link.callback(move |_| Msg::Resign)>{"RESIGN"}</button>}
+					} else {
+						html! {<button class="selection__reset" onclick=self.link.callback(move |_| Msg::Reset)>{"RESET"}</button>}
+					}
+				}
+				<button class="selection__hint" disabled={self.board.termination || self.review.is_some() || self.online.is_some()} onclick=self.link.callback(move |_| Msg::Hint)>{"HINT"}</button>
+				<button class="selection__hint" disabled={self.online.is_some() && !self.board.termination} onclick=self.link.callback(move |_| Msg::ToggleEvaluation)>
+					{if self.evaluation.is_some() { "HIDE EVAL" } else { "SHOW EVAL" }}
+				</button>
+				<button class="selection__hint" disabled={!self.board.termination || self.review.is_some()} onclick=self.link.callback(move |_| Msg::Review)>{"REVIEW"}</button>
 				{opponent_buttons()}
+				{level_picker()}
+				{evaluator_picker()}
+				{seed_picker()}
 			</div>
-				<div class={format!("board {}", board_border_class())}>
+				<div class={format!("board {}", board_class(&self.board))}>
 				{
 					(0..NUM_COLS).into_iter().map(|col| {
 						return html! {
-							<div class={format!("{}", hover_col_class())} onclick=self.link.callback(move |_| Msg::MakeMove(col))>
+							<div class={hover_col_class(col)} onclick=self.link.callback(move |_| Msg::MakeMove(col))>
 								{
 									(0..NUM_ROWS).into_iter().map(|row| {
 										return html! {
 											<div class="cell">
-												{check_piece(row, col)}
+												{view_piece(&self.board, row, col)}
 											</div>
 										}
 									}).collect::<Html>()
@@ -255,7 +802,9 @@ impl Component for Connect4Page {
 					}).collect::<Html>()
 				}
 				</div>
+				{evaluation_bar()}
 				{game_status()}
+				{review_panel()}
 				
 			</div>
 		}
@@ -0,0 +1,329 @@
+// Game History: the logged in player's finished games, newest first
+use game_core::record::Ai;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+use yew::services::fetch::{FetchService, FetchTask, Request, Response};
+use yew::{prelude::*, format::{Json, Nothing}};
+use crate::switch::{AppRoute, RouterAnchor, RouterButton};
+use crate::types::session;
+
+const DAY_MILLIS: f64 = 24.0 * 60.0 * 60.0 * 1000.0;
+
+// Struct for History Page:
+pub struct History {
+    link: ComponentLink<Self>,
+    username: String,
+    filter: Filter,
+    page: u64,
+    history: Option<GameHistory>,
+    fetch_task: Option<FetchTask>,
+}
+
+// What the list is narrowed down to; empty strings filter nothing
+#[derive(Default)]
+struct Filter {
+    game: String,
+    level: String,
+    result: String,
+    // Dates as the date inputs give them, YYYY-MM-DD
+    from: String,
+    to: String,
+}
+
+// Message passing for history:
+pub enum Message {
+    SetGame(String),
+    SetLevel(String),
+    SetResult(String),
+    SetFrom(String),
+    SetTo(String),
+    Page(u64),
+    ReceiveResponse(Result<GameHistory, anyhow::Error>),
+}
+
+// One finished game
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub id: String,
+    pub game: String,
+    pub variant: String,
+    pub players: Vec<String>,
+    pub ai: Option<Ai>,
+    pub result: u8,
+    pub length: usize,
+    pub started_at: i64,
+    pub ended_at: i64,
+}
+
+// A page of games
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameHistory {
+    pub games: Vec<GameSummary>,
+    pub page: u64,
+    pub per_page: u64,
+    pub total: u64,
+}
+
+// Milliseconds since the Unix epoch at the start of a YYYY-MM-DD date, `None` for no date
+fn date_millis(date: &str) -> Option<f64> {
+    let millis = js_sys::Date::parse(date);
+    if date.is_empty() || millis.is_nan() { None } else { Some(millis) }
+}
+
+pub fn game_name(game: &str) -> &str {
+    match game {
+        "connect4" => "Connect 4",
+        "toot" => "TOOT & OTTO",
+        _ => "Unknown",
+    }
+}
+
+// Who `player` played; online games, matched or in a private room, name the other player
+pub fn opponent_name(game: &GameSummary, player: &str) -> String {
+    match (game.variant.as_str(), game.ai) {
+        ("online" | "private", None) => game.players.iter().find(|other| *other != player).cloned().unwrap_or_default(),
+        (_, None) => String::from("Human"),
+        (_, Some(Ai::Perfect)) => String::from("Expert+"),
+        ("adaptive", Some(Ai::Level(level))) => format!("Adaptive, level {:.1}", level),
+        (_, Some(Ai::Level(level))) => format!("Computer, level {}", level),
+    }
+}
+
+fn result_name(result: u8) -> &'static str {
+    match result {
+        0 => "Lost",
+        1 => "Won",
+        _ => "Tie",
+    }
+}
+
+impl History {
+    // Request to server to fetch the current page of games
+    fn get_games(&mut self) {
+        let authorization = match session::authorization() {
+            Some(authorization) => authorization,
+            None => return,
+        };
+
+        let mut query = vec![format!("page={}", self.page)];
+        for (name, value) in [("game", &self.filter.game), ("level", &self.filter.level), ("result", &self.filter.result)] {
+            if !value.is_empty() {
+                query.push(format!("{}={}", name, value));
+            }
+        }
+        if let Some(from) = date_millis(&self.filter.from) {
+            query.push(format!("from={}", from as i64));
+        }
+        // The end date is included, so the range stops at the start of the next day
+        if let Some(to) = date_millis(&self.filter.to) {
+            query.push(format!("to={}", (to + DAY_MILLIS) as i64));
+        }
+
+        let url = format!("http://localhost:8000/games/{}?{}", String::from(js_sys::encode_uri_component(&self.username)), query.join("&"));
+        let request = Request::get(url)
+            .header("Authorization", authorization)
+            .body(Nothing)
+            .expect("Build Request Failed");
+        let callback = self.link.callback(
+            |response: Response<Json<Result<GameHistory, anyhow::Error>>>| {
+                let Json(data) = response.into_body();
+                Message::ReceiveResponse(data)
+            },
+        );
+        let task = FetchService::fetch(request, callback).expect("failed to start request");
+        self.fetch_task = Some(task);
+    }
+
+    // A changed filter starts again from the first page
+    fn refilter(&mut self) {
+        self.page = 1;
+        self.get_games();
+    }
+
+    fn view_filters(&self) -> Html {
+        html! {
+            <div class="history__filters">
+                <select onchange=self.link.callback(|e: ChangeData| match e {
+                    ChangeData::Select(select) => Message::SetGame(select.value()),
+                    _ => Message::SetGame(String::new()),
+                })>
+                    <option value="" selected=self.filter.game.is_empty()>{"All games"}</option>
+                    <option value="connect4" selected=(self.filter.game == "connect4")>{"Connect 4"}</option>
+                    <option value="toot" selected=(self.filter.game == "toot")>{"TOOT & OTTO"}</option>
+                </select>
+                <select onchange=self.link.callback(|e: ChangeData| match e {
+                    ChangeData::Select(select) => Message::SetLevel(select.value()),
+                    _ => Message::SetLevel(String::new()),
+                })>
+                    <option value="" selected=self.filter.level.is_empty()>{"All levels"}</option>
+                    {
+                        for (1..=10).map(|level| html! {
+                            <option value=level.to_string() selected=(self.filter.level == level.to_string())>{format!("Level {}", level)}</option>
+                        })
+                    }
+                </select>
+                <select onchange=self.link.callback(|e: ChangeData| match e {
+                    ChangeData::Select(select) => Message::SetResult(select.value()),
+                    _ => Message::SetResult(String::new()),
+                })>
+                    <option value="" selected=self.filter.result.is_empty()>{"All results"}</option>
+                    <option value="won" selected=(self.filter.result == "won")>{"Won"}</option>
+                    <option value="lost" selected=(self.filter.result == "lost")>{"Lost"}</option>
+                    <option value="tie" selected=(self.filter.result == "tie")>{"Tie"}</option>
+                </select>
+                <label class="history__date">{"From"}
+                    <input type="date" value=self.filter.from.clone()
+                        onchange=self.link.callback(|e: ChangeData| match e {
+                            ChangeData::Value(value) => Message::SetFrom(value),
+                            _ => Message::SetFrom(String::new()),
+                        })/>
+                </label>
+                <label class="history__date">{"To"}
+                    <input type="date" value=self.filter.to.clone()
+                        onchange=self.link.callback(|e: ChangeData| match e {
+                            ChangeData::Value(value) => Message::SetTo(value),
+                            _ => Message::SetTo(String::new()),
+                        })/>
+                </label>
+            </div>
+        }
+    }
+
+    fn view_games(&self, history: &GameHistory) -> Html {
+        if history.games.is_empty() {
+            return html! { <h2 class="stats_status">{"No Games Found"}</h2> };
+        }
+
+        let per_page = history.per_page.max(1);
+        let pages = ((history.total + per_page - 1) / per_page).max(1);
+        let page = history.page;
+        html! {
+            <>
+                <table class="history__table">
+                    <tr>
+                        <th>{"Date"}</th>
+                        <th>{"Game"}</th>
+                        <th>{"Opponent"}</th>
+                        <th>{"Result"}</th>
+                        <th>{"Moves"}</th>
+                        <th></th>
+                    </tr>
+                    {
+                        for history.games.iter().map(|game| html! {
+                            <tr>
+                                <td>{String::from(js_sys::Date::new(&JsValue::from_f64(game.ended_at as f64)).to_locale_string("en", &JsValue::UNDEFINED))}</td>
+                                <td>{game_name(&game.game)}</td>
+                                <td>{opponent_name(game, &self.username)}</td>
+                                <td class=format!("history__result history__result--{}", result_name(game.result).to_lowercase())>{result_name(game.result)}</td>
+                                <td>{game.length}</td>
+                                <td><RouterAnchor classes="history__replay" route=AppRoute::Replay(game.id.clone())>{"Replay"}</RouterAnchor></td>
+                            </tr>
+                        })
+                    }
+                </table>
+                <div class="history__pages">
+                    <button disabled=(page <= 1) onclick=self.link.callback(move |_| Message::Page(page - 1))>{"Newer"}</button>
+                    <span>{format!("Page {} of {}", page, pages)}</span>
+                    <button disabled=(page >= pages) onclick=self.link.callback(move |_| Message::Page(page + 1))>{"Older"}</button>
+                </div>
+            </>
+        }
+    }
+}
+
+impl Component for History {
+    type Message = Message;
+    type Properties = ();
+    // Create history component
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let loc_storage = web_sys::window().unwrap().local_storage().unwrap().unwrap();
+        let username = match loc_storage.get_item("LoggedIn") {
+            Ok(Some(name)) => name,
+            _ => "".to_string(),
+        };
+        Self {
+            link,
+            username,
+            filter: Filter::default(),
+            page: 1,
+            history: None,
+            fetch_task: None,
+        }
+    }
+
+    fn rendered(&mut self, first_render: bool) {
+        // Get the first page on first rendering
+        if first_render && self.username != "" {
+            self.get_games();
+        }
+    }
+
+    fn update(&mut self, message: Self::Message) -> ShouldRender {
+        match message {
+            Message::SetGame(game) => {
+                self.filter.game = game;
+                self.refilter();
+            }
+            Message::SetLevel(level) => {
+                self.filter.level = level;
+                self.refilter();
+            }
+            Message::SetResult(result) => {
+                self.filter.result = result;
+                self.refilter();
+            }
+            Message::SetFrom(from) => {
+                self.filter.from = from;
+                self.refilter();
+            }
+            Message::SetTo(to) => {
+                self.filter.to = to;
+                self.refilter();
+            }
+            Message::Page(page) => {
+                self.page = page.max(1);
+                self.get_games();
+            }
+            Message::ReceiveResponse(response) => {
+                self.history = response.ok();
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, _properties: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        // Obtain the Webpage View:
+        let content = if self.username == "" {
+            html! {
+                <div class="wrapper row">
+                    <h2 class="Brand">{"L.B.G.S."}</h2>
+                    <h2 class="login-prompt">{"Login Required for Game History"}</h2>
+                    <div class="col-md-12 button-div"><RouterButton route=AppRoute::Login>{"Login"}</RouterButton></div>
+                </div>
+            }
+        } else {
+            html! {
+                <div class="wrapper row">
+                    <p class="info col-md-12">{"Game\u{00a0}History"}</p>
+                    <div class="history">
+                        {self.view_filters()}
+                        {
+                            match &self.history {
+                                Some(history) => self.view_games(history),
+                                None => html! { <h2 class="stats_status">{"History Not Available"}</h2> },
+                            }
+                        }
+                    </div>
+                </div>
+            }
+        };
+
+        html! {
+            <div class="stats_page bg">{content}</div>
+        }
+    }
+}
@@ -0,0 +1,325 @@
+// Replay: steps through a stored game on the board it was played on
+use crate::{
+	components::{
+		connect4_page,
+		history::{game_name, opponent_name, GameSummary},
+		toot_and_otto_page,
+	},
+	connect4::connect4::{self, Connect4},
+	toot_and_otto::toot_and_otto::{self, TootAndOtto, TOenum},
+	switch::{AppRoute, RouterButton},
+	types::session,
+};
+use game_core::record;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use yew::services::fetch::{FetchService, FetchTask, Request, Response};
+use yew::services::interval::{IntervalService, IntervalTask};
+use yew::{prelude::*, format::{Json, Nothing}};
+
+/// Autoplay speeds on offer, in milliseconds per move
+const SPEEDS: [u64; 4] = [2000, 1000, 500, 250];
+
+/// Autoplay speed a replay starts at
+const DEFAULT_SPEED: u64 = 1000;
+
+/// A finished game with its moves, as the server stores it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoredGame {
+	#[serde(flatten)]
+	pub summary: GameSummary,
+	pub moves: Vec<String>,
+}
+
+/// The moves of a game, read from their notation
+enum Moves {
+	Connect4(Vec<usize>),
+	Toot(Vec<(usize, TOenum)>),
+}
+
+impl Moves {
+	/// `None` when the game is of no known kind or a move cannot be read
+	fn parse(game: &StoredGame) -> Option<Moves> {
+		match game.summary.game.as_str() {
+			"connect4" => game.moves.iter().map(|mv| record::parse_connect4(mv)).collect::<Option<_>>().map(Moves::Connect4),
+			"toot" => game.moves.iter().map(|mv| record::parse_toot(mv)).collect::<Option<_>>().map(Moves::Toot),
+			_ => None,
+		}
+	}
+
+	fn len(&self) -> usize {
+		match self {
+			Moves::Connect4(moves) => moves.len(),
+			Moves::Toot(moves) => moves.len(),
+		}
+	}
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+	/// Hex id of the game in the games collection
+	pub id: String,
+}
+
+pub struct Replay {
+	link: ComponentLink<Self>,
+	id: String,
+	game: Option<(StoredGame, Moves)>,
+	/// Moves played on the board shown
+	step: usize,
+	/// Milliseconds between moves while playing
+	speed: u64,
+	/// Ticks while autoplay runs
+	play_task: Option<IntervalTask>,
+	fetch_task: Option<FetchTask>,
+	/// Whether the server has answered, so a missing game can be told from a loading one
+	loaded: bool,
+}
+
+pub enum Msg {
+	Step(usize),
+	TogglePlay,
+	SetSpeed(u64),
+	Tick,
+	ReceiveResponse(Result<Option<StoredGame>, anyhow::Error>),
+}
+
+impl Replay {
+	/// Request to server to fetch the game
+	fn get_game(&mut self) {
+		let authorization = match session::authorization() {
+			Some(authorization) => authorization,
+			None => return,
+		};
+
+		let url = format!("http://localhost:8000/game/{}", String::from(js_sys::encode_uri_component(&self.id)));
+		let request = Request::get(url)
+			.header("Authorization", authorization)
+			.body(Nothing)
+			.expect("Build Request Failed");
+		let callback = self.link.callback(
+			|response: Response<Json<Result<Option<StoredGame>, anyhow::Error>>>| {
+				let Json(data) = response.into_body();
+				Msg::ReceiveResponse(data)
+			},
+		);
+		let task = FetchService::fetch(request, callback).expect("failed to start request");
+		self.fetch_task = Some(task);
+	}
+
+	fn last(&self) -> usize {
+		self.game.as_ref().map_or(0, |(_, moves)| moves.len())
+	}
+
+	fn start_playing(&mut self) {
+		let task = IntervalService::spawn(Duration::from_millis(self.speed), self.link.callback(|_| Msg::Tick));
+		self.play_task = Some(task);
+	}
+
+	/// The board after the first `step` moves; the pages highlight the winning line once the game is over
+	fn view_board(&self, moves: &Moves) -> Html {
+		match moves {
+			Moves::Connect4(moves) => {
+				let mut board = Connect4::initialize();
+				for col in moves[..self.step].iter() {
+					board.place(*col);
+				}
+				html! {
+					<div class={format!("board {}", connect4_page::board_class(&board))}>
+					{
+						(0..connect4::NUM_COLS).into_iter().map(|col| html! {
+							<div class="column">
+							{
+								(0..connect4::NUM_ROWS).into_iter().map(|row| html! {
+									<div class="cell">{connect4_page::view_piece(&board, row, col)}</div>
+								}).collect::<Html>()
+							}
+							</div>
+						}).collect::<Html>()
+					}
+					</div>
+				}
+			}
+			Moves::Toot(moves) => {
+				let mut board = TootAndOtto::new();
+				for (col, letter) in moves[..self.step].iter() {
+					board.drop(*letter, *col);
+				}
+				html! {
+					<div class={format!("board {}", toot_and_otto_page::board_class(&board))}>
+					{
+						(0..toot_and_otto::NUM_COLS).into_iter().map(|col| html! {
+							<div class="column">
+							{
+								(0..toot_and_otto::NUM_ROWS).into_iter().map(|row| html! {
+									<div class="cell">{toot_and_otto_page::view_piece(&board, row, col)}</div>
+								}).collect::<Html>()
+							}
+							</div>
+						}).collect::<Html>()
+					}
+					</div>
+				}
+			}
+		}
+	}
+
+	fn view_controls(&self) -> Html {
+		let step = self.step;
+		let last = self.last();
+
+		html! {
+			<div class="review replay__controls">
+				<div class="review__controls">
+					<button onclick=self.link.callback(|_| Msg::Step(0))>{"<<"}</button>
+					<button onclick=self.link.callback(move |_| Msg::Step(step.saturating_sub(1)))>{"<"}</button>
+					<span class="review__position">{format!("Move {} / {}", step, last)}</span>
+					<button onclick=self.link.callback(move |_| Msg::Step(step + 1))>{">"}</button>
+					<button onclick=self.link.callback(move |_| Msg::Step(last))>{">>"}</button>
+				</div>
+				<div class="review__controls">
+					<button onclick=self.link.callback(|_| Msg::TogglePlay)>
+						{if self.play_task.is_some() { "Pause" } else { "Play" }}
+					</button>
+					<select onchange=self.link.callback(|e: ChangeData| match e {
+						ChangeData::Select(select) => Msg::SetSpeed(select.value().parse().unwrap_or(DEFAULT_SPEED)),
+						_ => Msg::SetSpeed(DEFAULT_SPEED),
+					})>
+						{
+							for SPEEDS.iter().map(|speed| html! {
+								<option value=speed.to_string() selected=(*speed == self.speed)>
+									{format!("{} moves/s", 1000.0 / *speed as f64)}
+								</option>
+							})
+						}
+					</select>
+				</div>
+			</div>
+		}
+	}
+
+	fn view_game(&self, game: &StoredGame, moves: &Moves) -> Html {
+		let summary = &game.summary;
+		let player = summary.players.first().cloned().unwrap_or_default();
+		let opponent = opponent_name(summary, &player);
+		let status = if self.step < moves.len() {
+			String::from("REPLAY")
+		} else {
+			match summary.result {
+				record::WON => format!("{} WINS", player.to_uppercase()),
+				record::LOST => format!("{} WINS", opponent.to_uppercase()),
+				_ => String::from("TIE GAME"),
+			}
+		};
+
+		html! {
+			<div class="container replay">
+				<div class="replay__players">{format!("{}: {} vs {}", game_name(&summary.game), player, opponent)}</div>
+				{self.view_board(moves)}
+				<div class="game_status">
+					<div class="game_status--text-field">{status}</div>
+				</div>
+				{self.view_controls()}
+			</div>
+		}
+	}
+}
+
+impl Component for Replay {
+	type Message = Msg;
+	type Properties = Props;
+
+	fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+		Self {
+			link,
+			id: props.id,
+			game: None,
+			step: 0,
+			speed: DEFAULT_SPEED,
+			play_task: None,
+			fetch_task: None,
+			loaded: false,
+		}
+	}
+
+	fn rendered(&mut self, first_render: bool) {
+		if first_render {
+			self.get_game();
+		}
+	}
+
+	fn update(&mut self, msg: Self::Message) -> ShouldRender {
+		match msg {
+			Msg::Step(step) => {
+				self.step = step.min(self.last());
+			}
+			Msg::TogglePlay => {
+				if self.play_task.is_some() {
+					self.play_task = None;
+				} else {
+					// Playing from the end starts over
+					if self.step >= self.last() {
+						self.step = 0;
+					}
+					self.start_playing();
+				}
+			}
+			Msg::SetSpeed(speed) => {
+				self.speed = speed;
+				if self.play_task.is_some() {
+					self.start_playing();
+				}
+			}
+			Msg::Tick => {
+				if self.step < self.last() {
+					self.step += 1;
+				}
+				if self.step >= self.last() {
+					self.play_task = None;
+				}
+			}
+			Msg::ReceiveResponse(response) => {
+				self.loaded = true;
+				self.game = response.ok().flatten().and_then(|game| Moves::parse(&game).map(|moves| (game, moves)));
+				self.step = 0;
+				self.play_task = None;
+			}
+		}
+		true
+	}
+
+	fn change(&mut self, props: Self::Properties) -> ShouldRender {
+		if self.id == props.id {
+			return false;
+		}
+		self.id = props.id;
+		self.game = None;
+		self.loaded = false;
+		self.play_task = None;
+		self.get_game();
+		true
+	}
+
+	fn view(&self) -> Html {
+		if session::authorization().is_none() {
+			return html! {
+				<div class="stats_page bg">
+					<div class="wrapper row">
+						<h2 class="Brand">{"L.B.G.S."}</h2>
+						<h2 class="login-prompt">{"Login Required for Replays"}</h2>
+						<div class="col-md-12 button-div"><RouterButton route=AppRoute::Login>{"Login"}</RouterButton></div>
+					</div>
+				</div>
+			};
+		}
+
+		match &self.game {
+			Some((game, moves)) => self.view_game(game, moves),
+			None => html! {
+				<div class="stats_page bg">
+					<h2 class="stats_status">{if self.loaded { "Game Not Found" } else { "Loading Game" }}</h2>
+				</div>
+			},
+		}
+	}
+}
@@ -27,6 +27,7 @@ impl Component for SideBar {
 					<RouterButton route=AppRoute::Connect4> {"CONNECT4"} </RouterButton>
 					<RouterButton route=AppRoute::TootAndOtto> {"TOOT&OTTO"} </RouterButton>
 					<RouterButton route=AppRoute::Stats> {"STATS"} </RouterButton>
+					<RouterButton route=AppRoute::History> {"HISTORY"} </RouterButton>
 					<RouterButton route=AppRoute::Login> {"AUTH"} </RouterButton>
 				</div>
 			</div>
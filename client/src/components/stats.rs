@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use yew::services::fetch::{FetchService, FetchTask, Request, Response};
 use yew::{prelude::*, format::{Json, Nothing}};
 use crate::switch::{AppRoute, RouterButton};
+use crate::types::session;
 
 // Struct for Stats Page:
 pub struct Stats {
@@ -57,9 +58,15 @@ impl Stats {
         if user == "" {
             return;
         }
+        // The server answers with the stats of whoever the session belongs to
+        let authorization = match session::authorization() {
+            Some(authorization) => authorization,
+            None => return,
+        };
         log::info!("User {}", user);
-        let request = Request::get(format!("http://localhost:8000/scores/{}", &user))
+        let request = Request::get("http://localhost:8000/scores")
             .header("Content-Type", "application/json")
+            .header("Authorization", authorization)
             .body(Nothing)
             .expect("Build Request Failed");
         // Creat a callback that will receive a turple of data:
@@ -3,18 +3,71 @@ use crate::{
 		toot_ai,
 		toot_and_otto::{TootAndOtto, Player::*, NUM_COLS, NUM_ROWS, TOenum, TOenum::*},
 	},
-	types::opponent::Opponent,
+	types::{
+		adaptive::AdaptiveLevel,
+		analysis::{advantage, Limit},
+		online::{Online, OnlineEvent},
+		opponent::{Opponent, DEFAULT_LEVEL},
+		review::Review,
+		session,
+		strength::{level_elo, MAX_LEVEL},
+	},
 };
+use game_core::online::ServerMessage;
+use game_core::record::{self, StartedGame};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 use serde_json::json;
 use strum::IntoEnumIterator;
 use yew::format::Json;
 use yew::services::fetch::{FetchService, FetchTask, Request, Response};
-use yew::{html, Component, ComponentLink, Html, ShouldRender};
+use yew::{html, Component, ComponentLink, Html, InputData, Properties, ShouldRender};
+
+/// Local storage name of this game's adaptive level
+const GAME: &str = "toot";
+
+/// How long the hint and the evaluation bar search
+const ANALYSIS_TIME: Limit = Limit::Millis(500);
+
+/// How deep the post-game review searches each position
+const REVIEW_DEPTH: Limit = Limit::Depth(3);
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+	/// Code of the private room to join, from a `/toot-n-otto/room/<code>` link
+	#[prop_or_default]
+	pub room: Option<String>,
+}
 
 pub struct TootAndOttoPage {
 	link: ComponentLink<Self>,
 	board: TootAndOtto,
 	vs: Opponent,
+	adaptive: AdaptiveLevel,
+	/// Move the last hint recommended, cleared once a piece is dropped
+	hint: Option<(usize, TOenum)>,
+	/// Whether a hint was asked for during this game
+	hinted: bool,
+	/// TOOT's share of the evaluation bar, `None` while the bar is hidden
+	evaluation: Option<f64>,
+	/// Letters and columns played this game, in order
+	moves: Vec<(usize, TOenum)>,
+	/// Judged moves once a finished game is being reviewed
+	review: Option<Review<(usize, TOenum)>>,
+	/// How many moves of the reviewed game are on the board
+	review_step: usize,
+	/// When the first move was played, in milliseconds since the Unix epoch
+	started_at: f64,
+	/// Seed of this game's AI choices; entering it before the first move replays the game
+	seed: u64,
+	rng: StdRng,
+	/// Id of the game the server started with `seed`, without which the game does not score
+	game_id: Option<String>,
+	start_task: Option<FetchTask>,
+	/// The live game while the opponent is `Opponent::Online`
+	online: Option<Online>,
+	/// The room the page was opened to join
+	room: Option<String>,
 	fetch_task: Option<FetchTask>,
 }
 
@@ -22,25 +75,188 @@ pub enum Msg {
 	DropPiece(TOenum, usize),
 	Reset,
 	ChangeOpponent(Opponent),
+	Hint,
+	ToggleEvaluation,
+	Review,
+	ReviewStep(usize),
+	ChangeSeed(u64),
+	GameStarted(Option<StartedGame>),
+	Online(OnlineEvent),
+	Resign,
 	ReceiveResponse(Result<String, anyhow::Error>),
 }
 
+/// The letter in one cell of `board`, highlighted when it is part of a winning word
+pub fn view_piece(board: &TootAndOtto, row: usize, col: usize) -> Html {
+	let mut classes = String::from("piece");
+
+	match board.check_win(TOOT) {
+		None => {}
+		Some(coordinates) => {
+			if coordinates.contains(&[row, col]) {
+				classes.push_str(" piece--winner");
+			}
+		}
+	}
+
+	match board.check_win(OTTO) {
+		None => {}
+		Some(coordinates) => {
+			if coordinates.contains(&[row, col]) {
+				classes.push_str(" piece--winner");
+			}
+		}
+	}
+
+	classes.push_str(match board.board[row][col] {
+		None => " piece--empty",
+		Some(letter) => match letter {
+			T => " piece--toot-n-otto",
+			O => " piece--toot-n-otto",
+		},
+	});
+
+	let letter = match board.board[row][col] {
+		None => String::from(""),
+		Some(l) => format!("{}", l),
+	};
+
+	html! {<div class=classes>{letter}</div>}
+}
+
+/// Border of the board, in the colour of the player to move
+pub fn board_class(board: &TootAndOtto) -> &'static str {
+	match board.current_player {
+		TOOT => "board--p1",
+		OTTO => "board--p2",
+	}
+}
+
+/// Seat of the player to move, as live games count them
+fn seat_to_move(board: &TootAndOtto) -> usize {
+	(board.current_player == OTTO) as usize
+}
+
 impl TootAndOttoPage {
-	fn update_score(&mut self, result: u8, human_flag: u8) {
-		let ls = web_sys::window().unwrap().local_storage().unwrap().unwrap();
-		let username = match ls.get_item("LoggedIn") {
-			Ok(a) => match a {
-				Some(b) => b,
-				None => "".to_string(),
-			},
-			Err(_) => "".to_string(),
+	/// Records a finished game: sends the score and moves the adaptive level
+	fn finish_game(&mut self, result: u8) {
+		// The server replays the game at the level it was played at, so send it first
+		self.update_score(result);
+		if self.vs == Opponent::Adaptive {
+			// `result` is from the first player's side, which is always the human
+			let points = match result {
+				1 => 1.0,
+				0 => 0.0,
+				_ => 0.5,
+			};
+			self.adaptive.record(GAME, points);
+		}
+		self.refresh_analysis();
+	}
+
+	/// Connects to the server; the player joins the queue from there
+	fn connect_online(&mut self) {
+		self.online = Some(Online::connect(record::TOOT, self.link.callback(Msg::Online)));
+	}
+
+	/// Plays online in the room a friend shared the code of
+	fn join_room(&mut self, code: String) {
+		if let Some(online) = self.online.as_mut() {
+			online.leave();
+		}
+		self.vs = Opponent::Online;
+		self.evaluation = None;
+		self.online = Some(Online::join(record::TOOT, code, self.link.callback(Msg::Online)));
+	}
+
+	/// The board after the first `plies` moves of this game
+	fn replay(&self, plies: usize) -> TootAndOtto {
+		let mut board = TootAndOtto::new();
+		for (col, letter) in self.moves[..plies].iter() {
+			board.drop(*letter, *col);
+		}
+		board
+	}
+
+	/// Drops a stale hint and re-evaluates the position if the bar is shown
+	fn refresh_analysis(&mut self) {
+		self.hint = None;
+		if self.evaluation.is_some() {
+			self.evaluation = Some(self.evaluate());
+		}
+	}
+
+	/// TOOT's share of the evaluation bar for the current position
+	fn evaluate(&self) -> f64 {
+		if self.board.termination {
+			return match self.board.winner {
+				Some(TOOT) => 1.0,
+				Some(OTTO) => 0.0,
+				None => 0.5,
+			};
+		}
+
+		let share = match toot_ai::analyze(self.board, ANALYSIS_TIME).moves.first() {
+			Some(best) => advantage(best, toot_ai::EVALUATION_SCALE),
+			None => 0.5,
 		};
-		if username == "" {
-			return;
+		match self.board.current_player {
+			TOOT => share,
+			OTTO => 1.0 - share,
 		}
-		let body = &json!({"username": &username, "mode": 1, "result": result, "human_flag": human_flag});
+	}
+
+	/// Asks the server to start the next game against the computer. Its seed replaces
+	/// the page's own, and only a game the server started scores
+	fn start_game(&mut self) {
+		self.game_id = None;
+		self.start_task = None;
+		let ai = match self.vs.ai(&self.adaptive) {
+			Some(ai) => ai,
+			None => return,
+		};
+		let authorization = match session::authorization() {
+			Some(authorization) => authorization,
+			None => return,
+		};
+		let body = &json!({
+			"mode": record::TOOT,
+			"ai": ai,
+		});
+		let request = Request::post("http://localhost:8000/start_game")
+			.header("Content-Type", "application/json")
+			.header("Authorization", authorization)
+			.body(Json(body))
+			.expect("Build Request Failed");
+		let callback = self.link.callback(|response: Response<Json<Result<Option<StartedGame>, anyhow::Error>>>| {
+			let Json(data) = response.into_body();
+			Msg::GameStarted(data.ok().flatten())
+		});
+		self.start_task = FetchService::fetch(request, callback).ok();
+	}
+
+	fn update_score(&mut self, result: u8) {
+		// Scores go to whoever the session belongs to; without one nothing is recorded
+		let authorization = match session::authorization() {
+			Some(authorization) => authorization,
+			None => return,
+		};
+		// The whole game goes along, so the server can replay it and check the result
+		let body = &json!({
+			"mode": record::TOOT,
+			"moves": self.moves.iter().map(|mv| record::toot_notation(*mv)).collect::<Vec<_>>(),
+			"ai": self.vs.ai(&self.adaptive),
+			"seed": self.seed,
+			"game_id": self.game_id,
+			"result": result,
+			"hinted": self.hinted as u8,
+			"variant": self.vs.variant(),
+			"started_at": self.started_at as i64,
+			"client_version": env!("CARGO_PKG_VERSION"),
+		});
 		let request = Request::post("http://localhost:8000/update_score")
 			.header("Content-Type", "application/json")
+			.header("Authorization", authorization)
 			.body(Json(body))
 			.expect("Build Request Failed");
 		let callback =
@@ -58,74 +274,209 @@ impl TootAndOttoPage {
 
 impl Component for TootAndOttoPage {
 	type Message = Msg;
-	type Properties = ();
-	fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
-		Self {
+	type Properties = Props;
+	fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+		let seed = rand::random();
+		let mut page = Self {
 			link,
 			board: TootAndOtto::new(),
 			vs: Opponent::Human,
+			adaptive: AdaptiveLevel::load(GAME),
+			hint: None,
+			hinted: false,
+			evaluation: None,
+			moves: Vec::new(),
+			started_at: 0.0,
+			review: None,
+			review_step: 0,
+			seed,
+			rng: StdRng::seed_from_u64(seed),
+			game_id: None,
+			start_task: None,
+			online: None,
+			room: props.room.clone(),
 			fetch_task: None,
+		};
+		if let Some(code) = props.room {
+			page.join_room(code);
 		}
+		page
 	}
 
 	fn update(&mut self, msg: Self::Message) -> ShouldRender {
-		let mut human_flag:u8 = 0;
 		match msg {
 			Msg::DropPiece(letter, col) => {
-				let difficulty = match self.vs {
-					Opponent::Human => {
-						human_flag = 1;
-						0
-					},
-					Opponent::EasyMode => 1,
-					Opponent::NormalMode => 2,
-					Opponent::ExpertMode => 3,
-				};
-				if self.board.termination == true {
+				// Online the server has the board: the move goes there and is dropped once it comes back
+				if let Some(online) = self.online.as_mut() {
+					let mut board = self.board;
+					if online.my_turn(seat_to_move(&self.board)) && board.drop(letter, col) {
+						online.play(record::toot_notation((col, letter)));
+					}
+					return false;
+				}
+				if self.board.termination == true || self.review.is_some() {
 					return false;
 				} else if self.board.drop(letter, col) == false {
 					return false;
 				}
+				if self.moves.is_empty() {
+					self.started_at = js_sys::Date::now();
+				}
+				self.moves.push((col, letter));
 
 				if self.board.termination {
 					match self.board.winner {
 						None => {
 							// TODO: Insert a tie into the db
-							self.update_score(2, human_flag);
+							self.finish_game(2);
 						}
 						Some(winner) => match winner {
-							OTTO => self.update_score(0, human_flag),
-							TOOT => self.update_score(1, human_flag),
+							OTTO => self.finish_game(0),
+							TOOT => self.finish_game(1),
 						},
 					}
 					return true;
 				}
 
-				if difficulty != 0 {
-					let (best_col, best_letter) = toot_ai::AI_next_move(self.board, difficulty);
+				if self.vs == Opponent::PerfectMode {
+					let (best_col, best_letter) = toot_ai::perfect_move(self.board);
 					self.board.drop(best_letter, best_col);
+					self.moves.push((best_col, best_letter));
+				} else if let Some(strength) = self.vs.strength(&self.adaptive) {
+					if let Some((best_col, best_letter)) = toot_ai::ai_next_move(self.board, strength, &mut self.rng) {
+						self.board.drop(best_letter, best_col);
+						self.moves.push((best_col, best_letter));
+					}
 				}
 				if self.board.termination {
 					match self.board.winner {
 						None => {
 							// TODO: Insert a tie into the db
-							self.update_score(2, human_flag);
+							self.finish_game(2);
 						}
 						Some(winner) => match winner {
-							OTTO => self.update_score(0, human_flag),
-							TOOT => self.update_score(1, human_flag),
+							OTTO => self.finish_game(0),
+							TOOT => self.finish_game(1),
 						},
 					}
 					return true;
 				}
+				self.refresh_analysis();
+			}
+			Msg::ChangeSeed(seed) => {
+				if self.board.next_step == 0 && self.review.is_none() {
+					self.seed = seed;
+					self.rng = StdRng::seed_from_u64(seed);
+					// A replayed seed is the player's own, so the game no longer scores
+					self.game_id = None;
+					self.start_task = None;
+				}
+			}
+			Msg::GameStarted(started) => {
+				// Too late once the game is under way; it plays on with the page's seed
+				let started = match started {
+					Some(started) if self.moves.is_empty() && self.review.is_none() => started,
+					_ => return false,
+				};
+				self.seed = started.seed;
+				self.rng = StdRng::seed_from_u64(started.seed);
+				self.game_id = Some(started.id);
 			}
 			Msg::ChangeOpponent(opponent) => {
-				if self.board.next_step == 0 {
+				if self.board.next_step == 0 && self.review.is_none() {
+					if let Some(online) = self.online.as_mut() {
+						online.leave();
+					}
+					self.online = None;
 					self.vs = opponent;
+					if opponent == Opponent::Online {
+						self.evaluation = None;
+						self.connect_online();
+					}
+					self.start_game();
 				}
 			}
 			Msg::Reset => {
+				// An online game in progress can only be resigned
+				if self.online.as_ref().is_some_and(|online| online.playing()) {
+					return false;
+				}
 				self.board = TootAndOtto::new();
+				self.seed = rand::random();
+				self.rng = StdRng::seed_from_u64(self.seed);
+				self.hinted = false;
+				self.moves.clear();
+				self.review = None;
+				self.refresh_analysis();
+				self.start_game();
+				if let Some(online) = self.online.as_mut() {
+					online.reset();
+				}
+			}
+			Msg::Hint => {
+				if self.board.termination || self.review.is_some() || self.online.is_some() {
+					return false;
+				}
+				self.hint = toot_ai::analyze(self.board, ANALYSIS_TIME).moves.first().map(|best| best.mv);
+				self.hinted = true;
+			}
+			Msg::ToggleEvaluation => {
+				if self.online.is_some() && !self.board.termination {
+					return false;
+				}
+				self.evaluation = match self.evaluation {
+					Some(_) => None,
+					None => Some(self.evaluate()),
+				};
+			}
+			Msg::Review => {
+				if !self.board.termination || self.review.is_some() {
+					return false;
+				}
+				self.review = Some(toot_ai::review(&self.moves, REVIEW_DEPTH));
+				self.review_step = self.moves.len();
+			}
+			Msg::ReviewStep(step) => {
+				let step = step.min(self.moves.len());
+				self.review_step = step;
+				self.board = self.replay(step);
+				self.refresh_analysis();
+				// Show what the engine would have played next
+				self.hint = self.review.as_ref().and_then(|review| review.moves.get(step)).map(|judged| judged.best);
+			}
+			Msg::Online(event) => {
+				let message = match self.online.as_mut() {
+					Some(online) => online.handle(event),
+					None => return false,
+				};
+				match message {
+					// A new game, or the one the page reconnected to: set up the moves so far
+					Some(ServerMessage::Game(state)) => {
+						self.board = TootAndOtto::new();
+						self.moves.clear();
+						self.review = None;
+						self.started_at = js_sys::Date::now();
+						for (col, letter) in state.moves.iter().filter_map(|mv| record::parse_toot(mv)) {
+							self.board.drop(letter, col);
+							self.moves.push((col, letter));
+						}
+					}
+					Some(ServerMessage::Moved { mv, .. }) => {
+						if let Some((col, letter)) = record::parse_toot(&mv) {
+							if self.board.drop(letter, col) {
+								self.moves.push((col, letter));
+							}
+						}
+					}
+					_ => {}
+				}
+				self.refresh_analysis();
+			}
+			Msg::Resign => {
+				if let Some(online) = self.online.as_mut() {
+					online.resign();
+				}
+				return false;
 			}
 			Msg::ReceiveResponse(response) => match response.unwrap().as_str() {
 				"Update success" => {}
@@ -136,48 +487,18 @@ impl Component for TootAndOttoPage {
 		true
 	}
 
-	fn change(&mut self, _props: Self::Properties) -> ShouldRender {
-		false
+	fn change(&mut self, props: Self::Properties) -> ShouldRender {
+		if props.room == self.room {
+			return false;
+		}
+		self.room = props.room.clone();
+		if let Some(code) = props.room {
+			self.join_room(code);
+		}
+		true
 	}
 
 	fn view(&self) -> Html {
-		let check_piece = move |row: usize, col: usize| -> Html {
-			let mut classes = String::from("piece");
-
-			match self.board.check_win(TOOT) {
-				None => {}
-				Some(coordinates) => {
-					if coordinates.contains(&[row, col]) {
-						classes.push_str(" piece--winner");
-					}
-				}
-			}
-
-			match self.board.check_win(OTTO) {
-				None => {}
-				Some(coordinates) => {
-					if coordinates.contains(&[row, col]) {
-						classes.push_str(" piece--winner");
-					}
-				}
-			}
-
-			classes.push_str(match self.board.board[row][col] {
-				None => " piece--empty",
-				Some(letter) => match letter {
-					T => " piece--toot-n-otto",
-					O => " piece--toot-n-otto",
-				},
-			});
-
-			let letter = match self.board.board[row][col] {
-				None => String::from(""),
-				Some(l) => format!("{}", l),
-			};
-
-			html! {<div class=classes>{letter}</div>}
-		};
-
 		let game_status = move || -> Html {
 
 			let arrow_text = match (self.board.termination,self.board.current_player, self.vs, self.board.winner) {
@@ -192,6 +513,11 @@ impl Component for TootAndOttoPage {
 				(true, _,_ , Some(OTTO)) => "COMPUTER(OTTO) WINS",
 				(true, _, _, None) => "TIE GAME",
 			};
+			// Online the players go by their names
+			let arrow_text = match &self.online {
+				Some(online) => online.status_text(seat_to_move(&self.board)),
+				None => arrow_text.to_string(),
+			};
 
 			let text_color_class = move || -> &str {
 				match (self.board.termination,self.board.current_player, self.board.winner) {
@@ -213,7 +539,12 @@ impl Component for TootAndOttoPage {
 
 		let opponent_buttons = move || -> Html {
 			html! {
-				Opponent::iter().map(|opponent| {
+				Opponent::choices().iter().map(|opponent| {
+					// Keep the chosen level when the computer button is pressed again
+					let opponent = match (*opponent, self.vs) {
+						(Opponent::Computer(_), Opponent::Computer(level)) => Opponent::Computer(level),
+						(opponent, _) => opponent,
+					};
 					html! {
 						<button
 							class=format!("opponent__button {}", if self.vs == opponent {"opponent__button--selected"} else {""})
@@ -226,6 +557,59 @@ impl Component for TootAndOttoPage {
 			}
 		};
 
+		let legal_moves = &self.board.legal_moves();
+
+		let level_picker = move || -> Html {
+			match self.vs {
+				Opponent::Computer(level) => html! {
+					<div class="level">
+						<input
+							type="range"
+							class="level__slider"
+							min="1"
+							max=MAX_LEVEL.to_string()
+							value=level.to_string()
+							disabled={self.board.next_step > 0}
+							oninput=self.link.callback(|e: InputData| Msg::ChangeOpponent(Opponent::Computer(e.value.parse().unwrap_or(DEFAULT_LEVEL))))
+						/>
+						<span class="level__label">{format!("Level {} (~{} Elo)", level, toot_ai::LEVEL_ELO[level as usize - 1])}</span>
+					</div>
+				},
+				Opponent::Adaptive => html! {
+					<div class="level">
+						<span class="level__label">{format!(
+							"Adaptive level {:.1} (~{} Elo), you scored {:.0}% of your last {} games",
+							self.adaptive.level,
+							level_elo(&toot_ai::LEVEL_ELO, self.adaptive.level),
+							self.adaptive.win_rate() * 100.0,
+							self.adaptive.results.len(),
+						)}</span>
+					</div>
+				},
+				Opponent::Online => match &self.online {
+					Some(online) => online.view(),
+					None => html! {},
+				},
+				_ => html! {},
+			}
+		};
+
+		let seed_picker = move || -> Html {
+			html! {
+				<div class="seed">
+					<span class="seed__label">{"Seed"}</span>
+					<input
+						type="text"
+						class="seed__input"
+						inputmode="numeric"
+						value=self.seed.to_string()
+						disabled={self.board.next_step > 0}
+						oninput=self.link.callback(|e: InputData| Msg::ChangeSeed(e.value.parse().unwrap_or(0)))
+					/>
+				</div>
+			}
+		};
+
 		let floating_pieces = move |col: usize| -> Html {
 			let floating_piece_color =  move || -> &str {
 				match self.board.current_player {
@@ -235,18 +619,10 @@ impl Component for TootAndOttoPage {
 			};
 
 			let show_piece = move |letter| -> &str {
-				let player_index = match self.board.current_player {
-					TOOT => 0,
-					OTTO => 1,
-				};
-
-				let letter_index = match letter {
-					T => 0,
-					O => 1,
-				};
-
-				if self.board.countings[player_index][letter_index] == 0 {
-					return "piece--floating--hidden";
+				if !legal_moves.contains(&(col, letter)) {
+					return "piece--floating--disabled";
+				} else if self.hint == Some((col, letter)) {
+					return "piece--floating--hint";
 				} else {
 					return "";
 				}
@@ -266,22 +642,85 @@ impl Component for TootAndOttoPage {
 			}
 		};
 
-		let board_border_class = move || -> &str {
-			match self.board.current_player {
-				TOOT => "board--p1",
-				OTTO => "board--p2",
+		let review_panel = move || -> Html {
+			let review = match &self.review {
+				Some(review) => review,
+				None => return html! {},
+			};
+			let step = self.review_step;
+			let last = review.moves.len();
+
+			html! {
+				<div class="review">
+					<div class="review__controls">
+						<button onclick=self.link.callback(|_| Msg::ReviewStep(0))>{"<<"}</button>
+						<button onclick=self.link.callback(move |_| Msg::ReviewStep(step.saturating_sub(1)))>{"<"}</button>
+						<span class="review__position">{format!("Move {} / {}", step, last)}</span>
+						<button onclick=self.link.callback(move |_| Msg::ReviewStep(step + 1))>{">"}</button>
+						<button onclick=self.link.callback(move |_| Msg::ReviewStep(last))>{">>"}</button>
+					</div>
+					<ol class="review__moves">
+					{
+						review.moves.iter().enumerate().map(|(index, judged)| {
+							let mut classes = format!("review__move review__move--{}", judged.verdict.to_string().to_lowercase());
+							if index + 1 == step {
+								classes.push_str(" review__move--current");
+							}
+							let turning_point = review.turning_point == Some(index);
+							if turning_point {
+								classes.push_str(" review__move--turning-point");
+							}
+							let (col, letter) = judged.mv;
+							let (best_col, best_letter) = judged.best;
+
+							html! {
+								<li class=classes onclick=self.link.callback(move |_| Msg::ReviewStep(index + 1))>
+									{format!("{} {} in column {}", if index % 2 == 0 { TOOT } else { OTTO }, letter, col + 1)}
+									<span class="review__verdict">{judged.verdict}</span>
+									{if judged.best != judged.mv { format!("best was {} in column {}", best_letter, best_col + 1) } else { String::new() }}
+									{if turning_point { html! {<span class="review__turning-point">{"Turning point"}</span>} } else { html! {} }}
+								</li>
+							}
+						}).collect::<Html>()
+					}
+					</ol>
+				</div>
+			}
+		};
+
+		let evaluation_bar = move || -> Html {
+			match self.evaluation {
+				Some(share) => html! {
+					<div class="evaluation">
+						<div class="evaluation__p1" style=format!("width: {:.0}%", share * 100.0)></div>
+					</div>
+				},
+				None => html! {},
 			}
 		};
 
 		html! {
 			<div class="container">
 				<div class="selection">
-					<button class="selection__reset" onclick=self.link.callback(move |_| Msg::Reset)>{"RESET"}</button>
+					{
+						if self.online.as_ref().is_some_and(|online| online.playing()) {
+							html! {<button class="selection__reset" onclick=self.link.callback(move |_| Msg::Resign)>{"RESIGN"}</button>}
+						} else {
+							html! {<button class="selection__reset" onclick=self.link.callback(move |_| Msg::Reset)>{"RESET"}</button>}
+						}
+					}
+					<button class="selection__hint" disabled={self.board.termination || self.review.is_some() || self.online.is_some()} onclick=self.link.callback(move |_| Msg::Hint)>{"HINT"}</button>
+					<button class="selection__hint" disabled={self.online.is_some() && !self.board.termination} onclick=self.link.callback(move |_| Msg::ToggleEvaluation)>
+						{if self.evaluation.is_some() { "HIDE EVAL" } else { "SHOW EVAL" }}
+					</button>
+					<button class="selection__hint" disabled={!self.board.termination || self.review.is_some()} onclick=self.link.callback(move |_| Msg::Review)>{"REVIEW"}</button>
 					<div class=format!("opponent {}", if self.board.next_step > 0 { "opponent--disabled" } else { "" })>
 						{opponent_buttons()}
 					</div>
+					{level_picker()}
+					{seed_picker()}
 				</div>
-				<div class={format!("board {}", board_border_class())}>
+				<div class={format!("board {}", board_class(&self.board))}>
 					{
 						(0..NUM_COLS).into_iter().map(|col| {
 							return html! {
@@ -291,7 +730,7 @@ impl Component for TootAndOttoPage {
 										(0..NUM_ROWS).into_iter().map(|row| {
 											return html! {
 												<div class="cell">
-													{check_piece(row, col)}
+													{view_piece(&self.board, row, col)}
 												</div>
 											}
 										}).collect::<Html>()
@@ -301,7 +740,9 @@ impl Component for TootAndOttoPage {
 						}).collect::<Html>()
 					}
 				</div>
+				{evaluation_bar()}
 				{game_status()}
+				{review_panel()}
 				<div class="piece-counts__container">
 					<div class="piece-counts__p1">
 						<p id="left-info">{format!("TOOT - T's: {} O's: {}", self.board.countings[0][0], self.board.countings[0][1])}</p>
@@ -1,28 +1,27 @@
 #![recursion_limit = "1024"]
 
 mod app;
+mod ponder;
 mod switch;
 
 mod components {
     pub mod connect4_page;
     pub mod auth;
+    pub mod history;
+    pub mod replay;
     pub mod sidebar;
     pub mod stats;
     pub mod toot_and_otto_page;
 }
 
-mod connect4 {
-    pub mod connect4;
-    pub mod con4_ai;
-}
-
-mod toot_and_otto {
-    pub mod toot_ai;
-    pub mod toot_and_otto;
-}
+use game_core::{connect4, toot_and_otto};
 
 mod types {
+    pub use game_core::types::{analysis, eval_weights, review, strength};
+    pub mod adaptive;
+    pub mod online;
     pub mod opponent;
+    pub mod session;
 }
 
 use wasm_logger;
@@ -0,0 +1,172 @@
+use game_core::connect4::{
+	con4_ai::{self, Heuristic, ScoreTable},
+	connect4::Connect4,
+};
+use game_core::record::Evaluator;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::time::Duration;
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+use yew::worker::{Agent, AgentLink, HandlerId, Public};
+
+/// How deep the search that predicts the human's column looks
+const PREDICTION_DEPTH: usize = 2;
+
+/// Turns pondered past the one asked for, along the predicted line
+const TURNS_AHEAD: usize = 2;
+
+/// Pause between pondered replies, so the worker reads new requests in between
+const STEP_PAUSE: Duration = Duration::from_millis(0);
+
+/// What the Connect 4 page asks of the pondering worker
+#[derive(Debug, Serialize, Deserialize)]
+pub enum PonderRequest {
+	/// Ponder the AI's replies in the game after `moves`, the human to move, with
+	/// the search the AI runs at `depth`; replaces whatever was being pondered
+	Ponder { moves: Vec<usize>, depth: usize, evaluator: Evaluator },
+	/// Stop pondering, when the computer has no more moves to make
+	Stop,
+}
+
+/// The AI's reply to `col` in the game after `moves`, searched ahead of time:
+/// the scores `con4_ai::reply` would search for, at `depth` with `evaluator`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pondered {
+	pub moves: Vec<usize>,
+	pub col: usize,
+	pub depth: usize,
+	pub evaluator: Evaluator,
+	pub scores: Vec<(usize, i32)>,
+}
+
+/// Position being pondered and the human columns whose reply is still to search
+struct Job {
+	who: HandlerId,
+	moves: Vec<usize>,
+	board: Connect4,
+	depth: usize,
+	evaluator: Evaluator,
+	heuristic: Heuristic,
+	/// Human columns left, the predicted one first
+	columns: Vec<usize>,
+	/// The predicted column and the AI's best reply to it, once searched
+	predicted: Option<(usize, usize)>,
+	turns_ahead: usize,
+}
+
+impl Job {
+	fn new(who: HandlerId, moves: Vec<usize>, depth: usize, evaluator: Evaluator, table: &mut ScoreTable) -> Self {
+		let mut board = Connect4::initialize();
+		for col in moves.iter() {
+			board.place(*col);
+		}
+		let heuristic = evaluator.heuristic();
+		let mut job = Job { who, moves, board, depth, evaluator, heuristic, columns: Vec::new(), predicted: None, turns_ahead: 0 };
+		job.predict(table);
+		job
+	}
+
+	/// Orders the human's columns by how good they look to a shallow search
+	fn predict(&mut self, table: &mut ScoreTable) {
+		self.columns.clear();
+		self.predicted = None;
+		if self.board.termination {
+			return;
+		}
+		let mut predicted = con4_ai::score_moves_cached(self.board, PREDICTION_DEPTH, self.heuristic, table);
+		predicted.sort_by_key(|(_, score)| Reverse(*score));
+		self.columns = predicted.into_iter().map(|(col, _)| col).collect();
+	}
+
+	/// Moves on to the turn after the predicted column and reply, once every reply of this one is in
+	fn follow_prediction(&mut self, table: &mut ScoreTable) -> bool {
+		let (col, reply) = match self.predicted {
+			Some(predicted) if self.turns_ahead < TURNS_AHEAD => predicted,
+			_ => return false,
+		};
+		self.board.place(col);
+		self.board.place(reply);
+		self.moves.extend([col, reply]);
+		self.turns_ahead += 1;
+		self.predict(table);
+		!self.columns.is_empty()
+	}
+}
+
+/// Searches the AI's replies on its own thread while the human picks a column,
+/// keeping every searched position in one `ScoreTable` for the whole session
+pub struct Ponderer {
+	link: AgentLink<Self>,
+	table: ScoreTable,
+	job: Option<Job>,
+	step_task: Option<TimeoutTask>,
+}
+
+pub enum Msg {
+	Step,
+}
+
+impl Ponderer {
+	fn schedule_step(&mut self) {
+		self.step_task = Some(TimeoutService::spawn(STEP_PAUSE, self.link.callback(|_| Msg::Step)));
+	}
+
+	/// Searches the reply to the next column of the job and sends it to the page
+	fn step(&mut self) {
+		let job = match self.job.as_mut() {
+			Some(job) => job,
+			None => return,
+		};
+		if job.columns.is_empty() && !job.follow_prediction(&mut self.table) {
+			self.job = None;
+			return;
+		}
+
+		let col = job.columns.remove(0);
+		let mut reply_board = job.board;
+		if reply_board.place(col) && !reply_board.termination {
+			let scores = con4_ai::score_moves_cached(reply_board, job.depth, job.heuristic, &mut self.table);
+			if job.predicted.is_none() {
+				if let Some((reply, _)) = scores.iter().max_by_key(|(_, score)| *score) {
+					job.predicted = Some((col, *reply));
+				}
+			}
+			let pondered = Pondered { moves: job.moves.clone(), col, depth: job.depth, evaluator: job.evaluator, scores };
+			self.link.respond(job.who, pondered);
+		}
+		self.schedule_step();
+	}
+}
+
+impl Agent for Ponderer {
+	type Reach = Public<Self>;
+	type Message = Msg;
+	type Input = PonderRequest;
+	type Output = Pondered;
+
+	fn create(link: AgentLink<Self>) -> Self {
+		Ponderer { link, table: ScoreTable::new(con4_ai::classic()), job: None, step_task: None }
+	}
+
+	fn update(&mut self, msg: Self::Message) {
+		match msg {
+			Msg::Step => self.step(),
+		}
+	}
+
+	fn handle_input(&mut self, msg: Self::Input, who: HandlerId) {
+		self.step_task = None;
+		self.job = match msg {
+			PonderRequest::Ponder { moves, depth, evaluator } => Some(Job::new(who, moves, depth, evaluator, &mut self.table)),
+			PonderRequest::Stop => None,
+		};
+		if self.job.is_some() {
+			self.schedule_step();
+		}
+	}
+
+	/// Script Trunk builds from `src/bin/ponder_worker.rs`
+	fn name_of_resource() -> &'static str {
+		"ponder_worker.js"
+	}
+}
@@ -14,12 +14,20 @@ use yew_router::prelude::*;
 pub enum AppRoute {
 	#[to = "/stats/"]
 	Stats,
+	#[to = "/history/"]
+	History,
 	#[to = "/login/"]
 	Login,
+	#[to = "/connect-4/room/{code}"]
+	Connect4Room(String),
 	#[to = "/connect-4/"]
 	Connect4,
+	#[to = "/toot-n-otto/room/{code}"]
+	TootAndOttoRoom(String),
 	#[to = "/toot-n-otto/"]
 	TootAndOtto,
+	#[to = "/replay/{id}"]
+	Replay(String),
 	#[to = "/!"]
 	Home,
 }
@@ -97,3 +105,4 @@ impl Transformer<AppRoute, PublicUrlSwitch> for VComp {
 // Type aliases to make life just a bit easier
 pub type AppRouter = Router<PublicUrlSwitch>;
 pub type RouterButton = yew_router::components::RouterButton<AppRoute>;
+pub type RouterAnchor = yew_router::components::RouterAnchor<AppRoute>;
@@ -0,0 +1,71 @@
+// Adaptive opponent level, kept per user and per game in local storage
+use crate::types::strength::{Strength, MAX_LEVEL};
+use serde::{Deserialize, Serialize};
+
+/// How many of the player's latest games the level reacts to
+const RECENT_GAMES: usize = 10;
+
+/// How many levels a perfect (or hopeless) recent record moves the AI per game
+const STEP: f64 = 2.0;
+
+/// Level a player starts on before any adaptive game has been played
+const START_LEVEL: f64 = 5.0;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AdaptiveLevel {
+	/// Current, possibly fractional, AI level
+	pub level: f64,
+
+	/// The player's latest points, 1 for a win, 0.5 for a tie and 0 for a loss
+	pub results: Vec<f64>,
+}
+
+impl AdaptiveLevel {
+	/// Loads the logged-in user's level for `game`, or a fresh one
+	pub fn load(game: &str) -> Self {
+		local_storage()
+			.get_item(&storage_key(game))
+			.ok()
+			.flatten()
+			.and_then(|saved| serde_json::from_str(&saved).ok())
+			.unwrap_or(AdaptiveLevel { level: START_LEVEL, results: Vec::new() })
+	}
+
+	/// Records a finished game and moves the level towards a 50% win rate
+	pub fn record(&mut self, game: &str, points: f64) {
+		self.results.push(points);
+		if self.results.len() > RECENT_GAMES {
+			self.results.remove(0);
+		}
+
+		self.level = (self.level + STEP * (self.win_rate() - 0.5)).clamp(1.0, MAX_LEVEL as f64);
+
+		if let Ok(saved) = serde_json::to_string(self) {
+			local_storage().set_item(&storage_key(game), &saved).expect("Error saving adaptive level");
+		}
+	}
+
+	/// Share of the recent points the player took, 0.5 before any game
+	pub fn win_rate(&self) -> f64 {
+		if self.results.is_empty() {
+			return 0.5;
+		}
+		self.results.iter().sum::<f64>() / self.results.len() as f64
+	}
+
+	pub fn strength(&self) -> Strength {
+		Strength::from_level(self.level)
+	}
+}
+
+fn local_storage() -> web_sys::Storage {
+	web_sys::window().unwrap().local_storage().unwrap().unwrap()
+}
+
+fn storage_key(game: &str) -> String {
+	let username = match local_storage().get_item("LoggedIn") {
+		Ok(Some(name)) => name,
+		_ => "".to_string(),
+	};
+	format!("AdaptiveLevel:{}:{}", game, username)
+}
@@ -0,0 +1,356 @@
+// A live game against another player through the server's WebSocket. The game pages
+// keep one while their opponent is `Opponent::Online` and play every move it passes on
+use crate::{switch::AppRoute, types::session};
+use game_core::online::{loss_of, ClientMessage, EndReason, QueueStatus, ServerMessage};
+use game_core::record::{CONNECT4, TIE};
+use std::time::Duration;
+use yew::callback::Callback;
+use yew::format::Json;
+use yew::services::interval::{IntervalService, IntervalTask};
+use yew::services::timeout::{TimeoutService, TimeoutTask};
+use yew::services::websocket::{WebSocketService, WebSocketStatus, WebSocketTask};
+use yew::{html, Html};
+
+const LIVE_URL: &str = "ws://localhost:8001";
+
+/// Wait before reconnecting after the connection drops
+const RETRY: Duration = Duration::from_secs(2);
+
+/// How often the clocks on the page count down
+const CLOCK_TICK: Duration = Duration::from_secs(1);
+
+/// What the page passes back to `Online::handle`
+pub enum OnlineEvent {
+	Received(Result<ServerMessage, anyhow::Error>),
+	Status(WebSocketStatus),
+	Retry,
+	Tick,
+	/// The player pressed "Find opponent"
+	Find,
+	/// The player pressed "New room"
+	CreateRoom,
+	/// The player left the queue, or closed their room
+	Cancel,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum OnlineStatus {
+	Connecting,
+	/// Connected, not looking for a game
+	Idle,
+	/// In the matchmaking queue; `None` until the server says where
+	Queued(Option<QueueStatus>),
+	/// Waiting in a private room for a friend; `None` until the server gives its code
+	Hosting(Option<String>),
+	/// Taking the second seat of the room with this code
+	Joining(String),
+	Playing,
+	/// `result` is from the first player's side
+	Over { result: u8, reason: EndReason },
+	/// Not logged in, or the server turned the page down
+	Failed(String),
+}
+
+pub struct Online {
+	/// `record::CONNECT4` or `record::TOOT`
+	mode: u8,
+	pub status: OnlineStatus,
+	/// First player first, empty until a game starts
+	pub players: [String; 2],
+	pub ratings: [i32; 2],
+	/// False for a game in a private room, which leaves the ratings alone
+	rated: bool,
+	/// Which of `players` this page plays
+	pub seat: usize,
+	/// When the player to move runs out of time, in milliseconds since the Unix epoch
+	deadline: f64,
+	/// When the opponent forfeits unless they reconnect
+	away: Option<f64>,
+	/// Whether the server took the session token on this connection
+	welcomed: bool,
+	events: Callback<OnlineEvent>,
+	socket: Option<WebSocketTask>,
+	retry_task: Option<TimeoutTask>,
+	clock_task: Option<IntervalTask>,
+}
+
+/// Whole seconds until `deadline`, as m:ss
+fn countdown(deadline: f64) -> String {
+	let seconds = ((deadline - js_sys::Date::now()) / 1000.0).ceil().max(0.0) as u64;
+	format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+impl Online {
+	/// Connects for games of `mode`; a player who left a game in progress is put back in it
+	pub fn connect(mode: u8, events: Callback<OnlineEvent>) -> Online {
+		Online::start(mode, OnlineStatus::Connecting, events)
+	}
+
+	/// Connects and joins the room a friend shared the code of
+	pub fn join(mode: u8, code: String, events: Callback<OnlineEvent>) -> Online {
+		Online::start(mode, OnlineStatus::Joining(code), events)
+	}
+
+	fn start(mode: u8, status: OnlineStatus, events: Callback<OnlineEvent>) -> Online {
+		let mut online = Online {
+			mode,
+			status,
+			players: Default::default(),
+			ratings: Default::default(),
+			rated: true,
+			seat: 0,
+			deadline: 0.0,
+			away: None,
+			welcomed: false,
+			events,
+			socket: None,
+			retry_task: None,
+			clock_task: None,
+		};
+		online.open();
+		online
+	}
+
+	fn open(&mut self) {
+		let received = self.events.reform(|Json(data): Json<Result<ServerMessage, anyhow::Error>>| OnlineEvent::Received(data));
+		let notification = self.events.reform(OnlineEvent::Status);
+		match WebSocketService::connect_text(LIVE_URL, received, notification) {
+			Ok(task) => self.socket = Some(task),
+			Err(error) => self.status = OnlineStatus::Failed(error.to_string()),
+		}
+	}
+
+	fn send(&mut self, message: ClientMessage) {
+		if let Some(socket) = self.socket.as_mut() {
+			socket.send(Json(&message));
+		}
+	}
+
+	/// Whether a game is in progress
+	pub fn playing(&self) -> bool {
+		self.status == OnlineStatus::Playing
+	}
+
+	/// Whether the page may play a move while `to_move` (0 or 1) is the seat to move
+	pub fn my_turn(&self, to_move: usize) -> bool {
+		self.playing() && to_move == self.seat
+	}
+
+	/// Sends a move in notation; the board changes once the server passes it back
+	pub fn play(&mut self, mv: String) {
+		self.send(ClientMessage::Move { mv });
+	}
+
+	pub fn resign(&mut self) {
+		self.send(ClientMessage::Resign);
+	}
+
+	/// Leaves the queue or the room, or gives up the game in progress, before the page drops this
+	pub fn leave(&mut self) {
+		match self.status {
+			OnlineStatus::Queued(_) | OnlineStatus::Hosting(_) => self.send(ClientMessage::Cancel),
+			OnlineStatus::Playing => self.resign(),
+			_ => {}
+		}
+	}
+
+	/// Back from a finished game to looking for the next one
+	pub fn reset(&mut self) {
+		if let OnlineStatus::Over { .. } = self.status {
+			self.status = OnlineStatus::Idle;
+		}
+	}
+
+	/// Keeps track of the connection and the game; hands the page the
+	/// messages that change its board, `Game` and `Moved`
+	pub fn handle(&mut self, event: OnlineEvent) -> Option<ServerMessage> {
+		match event {
+			OnlineEvent::Status(WebSocketStatus::Opened) => match session::token() {
+				Some(token) => self.send(ClientMessage::Hello { token }),
+				None => self.status = OnlineStatus::Failed(String::from("Log in to play online")),
+			},
+			OnlineEvent::Status(_) => {
+				self.socket = None;
+				self.welcomed = false;
+				// The server keeps the seat for a while, so try to get back to it
+				if let OnlineStatus::Failed(_) = self.status {
+					return None;
+				}
+				self.retry_task = Some(TimeoutService::spawn(RETRY, self.events.reform(|_| OnlineEvent::Retry)));
+			}
+			OnlineEvent::Retry => {
+				self.retry_task = None;
+				self.open();
+			}
+			OnlineEvent::Tick => {}
+			OnlineEvent::Find => {
+				if let OnlineStatus::Idle | OnlineStatus::Over { .. } = self.status {
+					self.status = OnlineStatus::Queued(None);
+					// Before the server has logged the connection in, the welcome sends it
+					if self.welcomed {
+						self.send(ClientMessage::Seek { mode: self.mode });
+					}
+				}
+			}
+			OnlineEvent::CreateRoom => {
+				if let OnlineStatus::Idle | OnlineStatus::Over { .. } = self.status {
+					self.status = OnlineStatus::Hosting(None);
+					if self.welcomed {
+						self.send(ClientMessage::CreateRoom { mode: self.mode });
+					}
+				}
+			}
+			OnlineEvent::Cancel => {
+				if let OnlineStatus::Queued(_) | OnlineStatus::Hosting(_) = self.status {
+					self.send(ClientMessage::Cancel);
+					self.status = OnlineStatus::Idle;
+				}
+			}
+			OnlineEvent::Received(Ok(message)) => {
+				let now = js_sys::Date::now();
+				match &message {
+					// A player back in a game is sent it straight after; the queue forgets
+					// whoever drops, so a player who was in it joins again. A room waits for its
+					// host, who gets it back with its code
+					ServerMessage::Welcome { .. } => {
+						self.welcomed = true;
+						match self.status.clone() {
+							OnlineStatus::Queued(_) => self.send(ClientMessage::Seek { mode: self.mode }),
+							OnlineStatus::Hosting(None) => self.send(ClientMessage::CreateRoom { mode: self.mode }),
+							OnlineStatus::Hosting(Some(code)) | OnlineStatus::Joining(code) => self.send(ClientMessage::JoinRoom { code }),
+							OnlineStatus::Connecting => self.status = OnlineStatus::Idle,
+							_ => {}
+						}
+					}
+					ServerMessage::Room { code, .. } => {
+						if let OnlineStatus::Hosting(_) = self.status {
+							self.status = OnlineStatus::Hosting(Some(code.clone()));
+						}
+					}
+					ServerMessage::Queued(status) => {
+						if let OnlineStatus::Queued(_) = self.status {
+							self.status = OnlineStatus::Queued(Some(status.clone()));
+						}
+					}
+					ServerMessage::Game(state) => {
+						self.status = OnlineStatus::Playing;
+						self.players = state.players.clone();
+						self.ratings = state.ratings;
+						self.rated = state.rated;
+						self.seat = state.seat;
+						self.deadline = now + state.time_left as f64;
+						self.away = None;
+						self.clock_task = Some(IntervalService::spawn(CLOCK_TICK, self.events.reform(|_| OnlineEvent::Tick)));
+					}
+					ServerMessage::Moved { time_left, .. } => self.deadline = now + *time_left as f64,
+					ServerMessage::OpponentAway { time_left } => self.away = Some(now + *time_left as f64),
+					ServerMessage::OpponentBack => self.away = None,
+					ServerMessage::Over { result, reason } => {
+						self.status = OnlineStatus::Over { result: *result, reason: *reason };
+						self.away = None;
+						self.clock_task = None;
+					}
+					// Mid-game errors are about a single move, which the board never showed
+					ServerMessage::Error { message } => {
+						if self.status != OnlineStatus::Playing {
+							self.status = OnlineStatus::Failed(message.clone());
+						}
+					}
+				}
+				return Some(message);
+			}
+			OnlineEvent::Received(Err(_)) => {}
+		}
+		None
+	}
+
+	fn opponent(&self) -> &str {
+		&self.players[1 - self.seat]
+	}
+
+	/// Game status line of the page while playing online; `to_move` is the seat to move
+	pub fn status_text(&self, to_move: usize) -> String {
+		match &self.status {
+			OnlineStatus::Connecting => String::from("CONNECTING"),
+			OnlineStatus::Idle => String::from("PLAY ONLINE"),
+			OnlineStatus::Queued(_) => String::from("FINDING OPPONENT"),
+			OnlineStatus::Hosting(_) => String::from("WAITING FOR A FRIEND"),
+			OnlineStatus::Joining(_) => String::from("JOINING ROOM"),
+			OnlineStatus::Playing if to_move == self.seat => String::from("YOUR MOVE"),
+			OnlineStatus::Playing => format!("{} TO MOVE", self.opponent().to_uppercase()),
+			OnlineStatus::Over { result: TIE, .. } => String::from("TIE GAME"),
+			OnlineStatus::Over { result, .. } if *result == loss_of(self.seat) => format!("{} WINS", self.opponent().to_uppercase()),
+			OnlineStatus::Over { .. } => String::from("YOU WIN"),
+			OnlineStatus::Failed(_) => String::from("OFFLINE"),
+		}
+	}
+
+	/// Opponent's name, and their rating when the game is rated
+	fn versus(&self) -> String {
+		if self.rated {
+			format!("vs {} ({})", self.opponent(), self.ratings[1 - self.seat])
+		} else {
+			format!("vs {}, unrated", self.opponent())
+		}
+	}
+
+	/// Link a friend opens to join the room with this code
+	fn room_link(&self, code: &str) -> String {
+		let route = match self.mode {
+			CONNECT4 => AppRoute::Connect4Room(code.to_string()),
+			_ => AppRoute::TootAndOttoRoom(code.to_string()),
+		};
+		let origin = yew::utils::window().location().origin().unwrap_or_default();
+		format!("{}{}", origin, route.into_route().route)
+	}
+
+	/// Who is playing, the clock, how the game ended, and the queue or room with its buttons
+	pub fn view(&self) -> Html {
+		let detail = match &self.status {
+			OnlineStatus::Connecting => String::from("Connecting to the server"),
+			OnlineStatus::Idle => String::from("Play someone near your rating"),
+			OnlineStatus::Queued(None) => String::from("Joining the queue"),
+			OnlineStatus::Queued(Some(queue)) => {
+				let seconds = queue.waiting / 1000;
+				format!(
+					"Looking for {} to {} for {}:{:02}, {} in the queue",
+					queue.rating - queue.range, queue.rating + queue.range, seconds / 60, seconds % 60, queue.queued,
+				)
+			}
+			OnlineStatus::Hosting(None) => String::from("Opening a room"),
+			OnlineStatus::Hosting(Some(code)) => format!("Room {}: send a friend {}", code, self.room_link(code)),
+			OnlineStatus::Joining(code) => format!("Joining room {}", code),
+			OnlineStatus::Playing => match self.away {
+				Some(forfeit) => format!("{} lost their connection and forfeits in {}", self.opponent(), countdown(forfeit)),
+				None => format!("{}, {} left for the move", self.versus(), countdown(self.deadline)),
+			},
+			OnlineStatus::Over { reason, .. } => format!("{}, {}", self.versus(), match reason {
+				EndReason::Finished => "game over",
+				EndReason::Resigned => "ended by resignation",
+				EndReason::TimedOut => "ended on time",
+				EndReason::Abandoned => "ended when a player left",
+			}),
+			OnlineStatus::Failed(message) => message.clone(),
+		};
+
+		let button = match self.status {
+			OnlineStatus::Idle | OnlineStatus::Over { .. } => html! {
+				<>
+					<button class="opponent__button" onclick=self.events.reform(|_| OnlineEvent::Find)>{"FIND OPPONENT"}</button>
+					<button class="opponent__button" onclick=self.events.reform(|_| OnlineEvent::CreateRoom)>{"NEW ROOM"}</button>
+				</>
+			},
+			OnlineStatus::Queued(_) | OnlineStatus::Hosting(_) => html! {
+				<button class="opponent__button" onclick=self.events.reform(|_| OnlineEvent::Cancel)>{"CANCEL"}</button>
+			},
+			_ => html! {},
+		};
+
+		html! {
+			<div class="level">
+				<span class="level__label">{detail}</span>
+				{button}
+			</div>
+		}
+	}
+}
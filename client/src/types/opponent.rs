@@ -1,35 +1,84 @@
 use std::fmt::{Display, Formatter, Result};
-use strum_macros::EnumIter;
+use crate::types::{adaptive::AdaptiveLevel, strength::{Strength, MAX_LEVEL}};
+use game_core::record::Ai;
 
-#[derive(EnumIter, Copy, Clone)]
+/// Level the computer starts on when it is first picked
+pub const DEFAULT_LEVEL: u8 = 7;
+
+#[derive(Copy, Clone)]
 pub enum Opponent {
 	Human,
-	EasyMode,
-	NormalMode,
-	ExpertMode,
+	Computer(u8),
+	/// Computer whose level follows the player's results, see `AdaptiveLevel`
+	Adaptive,
+	PerfectMode,
+	/// Another player, through the server; see `Online`
+	Online,
+}
+
+impl Opponent {
+	/// The choices shown as buttons on the game pages
+	pub fn choices() -> [Opponent; 5] {
+		[Opponent::Human, Opponent::Computer(DEFAULT_LEVEL), Opponent::Adaptive, Opponent::PerfectMode, Opponent::Online]
+	}
+
+	/// How the computer plays, `None` for a human opponent; the adaptive
+	/// level is kept by the page, so `adaptive` is passed in
+	pub fn strength(&self, adaptive: &AdaptiveLevel) -> Option<Strength> {
+		match self {
+			Opponent::Human | Opponent::Online => None,
+			Opponent::Adaptive => Some(adaptive.strength()),
+			Opponent::Computer(level) => Some(Strength::from_level(*level as f64)),
+			Opponent::PerfectMode => Some(Strength::from_level(MAX_LEVEL as f64)),
+		}
+	}
+
+	/// How the game was played, as the server's game records name it
+	pub fn variant(&self) -> &'static str {
+		match self {
+			Opponent::Human => "local",
+			Opponent::Computer(_) => "computer",
+			Opponent::Adaptive => "adaptive",
+			Opponent::PerfectMode => "perfect",
+			Opponent::Online => "online",
+		}
+	}
+
+	/// The computer as a game record names it, `None` for a human opponent
+	pub fn ai(&self, adaptive: &AdaptiveLevel) -> Option<Ai> {
+		match self {
+			Opponent::Human | Opponent::Online => None,
+			Opponent::Adaptive => Some(Ai::Level(adaptive.level)),
+			Opponent::Computer(level) => Some(Ai::Level(*level as f64)),
+			Opponent::PerfectMode => Some(Ai::Perfect),
+		}
+	}
 }
 
 impl Display for Opponent {
-	/// Prints out the piece color
+	/// Prints out the opponent name
 	fn fmt(&self, f: &mut Formatter) -> Result {
 		match self {
 			Opponent::Human => write!(f, "{}", "Human"),
-			Opponent::EasyMode => write!(f, "{}", "Easy"),
-			Opponent::NormalMode => write!(f, "{}", "Normal"),
-			Opponent::ExpertMode => write!(f, "{}", "Expert"),
+			Opponent::Computer(_) => write!(f, "{}", "Computer"),
+			Opponent::Adaptive => write!(f, "{}", "Adaptive"),
+			Opponent::PerfectMode => write!(f, "{}", "Expert+"),
+			Opponent::Online => write!(f, "{}", "Online"),
 		}
 	}
 }
 
 impl PartialEq for Opponent {
+	/// Opponents of the same kind are equal, whatever the computer level
 	fn eq(&self, other: &Opponent) -> bool {
 		use Opponent::*;
 
 		match (self, other) {
 			(Human, Human) => true,
-			(EasyMode, EasyMode) => true,
-			(NormalMode, NormalMode) => true,
-			(ExpertMode, ExpertMode) => true,
+			(Computer(_), Computer(_)) => true,
+			(Adaptive, Adaptive) => true,
+			(PerfectMode, PerfectMode) => true,
+			(Online, Online) => true,
 			_ => false,
 		}
 	}
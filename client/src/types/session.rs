@@ -0,0 +1,31 @@
+// The logged in user and their session token, kept in local storage
+fn local_storage() -> web_sys::Storage {
+	web_sys::window().unwrap().local_storage().unwrap().unwrap()
+}
+
+/// Remembers a user the server just logged in
+pub fn save(username: &str, token: &str) {
+	let storage = local_storage();
+	storage.set_item("LoggedIn", username).expect("Error setting user login");
+	storage.set_item("SessionToken", token).expect("Error saving session token");
+}
+
+/// Forgets the logged in user
+pub fn clear() {
+	let storage = local_storage();
+	storage.set_item("LoggedIn", "").expect("Clear Failed");
+	storage.remove_item("SessionToken").expect("Clear Failed");
+}
+
+/// Session token of the logged in user
+pub fn token() -> Option<String> {
+	match local_storage().get_item("SessionToken") {
+		Ok(Some(token)) if !token.is_empty() => Some(token),
+		_ => None,
+	}
+}
+
+/// Value of the `Authorization` header for requests made as the logged in user
+pub fn authorization() -> Option<String> {
+	token().map(|token| format!("Bearer {}", token))
+}
@@ -0,0 +1,459 @@
+use super::{
+	con4_model::{self, LinearModel},
+	connect4::{Connect4, C4Piece, NUM_COLS, NUM_ROWS},
+};
+use crate::types::{
+	analysis::{deepen, Analysis, Deadline, Limit, MoveEval},
+	eval_weights::EvalWeights,
+	review::{judge, Review},
+	strength::Strength,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::collections::HashMap;
+#[cfg(feature = "node-count")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Positions `find_best_move` has visited, on every thread, since `take_nodes`
+#[cfg(feature = "node-count")]
+static NODES: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the positions the minimax visited since the last call and starts counting again
+#[cfg(feature = "node-count")]
+pub fn take_nodes() -> u64 
+{
+	NODES.swap(0, Ordering::Relaxed)
+}
+
+/// Approximate Elo of each level (index 0 is level 1), measured with the
+/// calibration mode of `game_test` and anchored so level 1 sits at 800
+pub const LEVEL_ELO: [i32; 10] = [800, 906, 1004, 1048, 1201, 1262, 1351, 1416, 1599, 1662];
+
+/// Heuristic score that fills about 73% of an evaluation bar
+pub const EVALUATION_SCALE: f64 = 100.0;
+
+/// The shipped heuristic weights, written by the tuner of `game_test`
+pub fn weights() -> EvalWeights 
+{
+	EvalWeights::from_json(include_str!("con4_weights.json")).expect("Error parsing con4_weights.json")
+}
+
+/// Which static evaluation the search uses at its leaves
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Heuristic 
+{
+	/// The window counting in `heuristic_searc_score`, with these weights
+	Classic(EvalWeights),
+	/// A logistic model from `con4_model`
+	Learned(&'static LinearModel),
+}
+
+/// The classic heuristic with the shipped weights
+pub fn classic() -> Heuristic 
+{
+	Heuristic::Classic(weights())
+}
+
+/// The learned evaluation shipped with the client
+pub fn learned() -> Heuristic 
+{
+	Heuristic::Learned(con4_model::learned())
+}
+
+/// Picks the AI's column, `None` once the game is over; every random choice is
+/// drawn from `rng`, so a seeded generator replays the same game
+pub fn ai_next_move<R: Rng>(board: Connect4, strength: Strength, heuristic: Heuristic, rng: &mut R) -> Option<usize> 
+{
+	reply(board, strength, rng, || {
+		// wasm32 has no threads to spread the columns over
+		#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+		let scores = score_moves_parallel(board, strength.depth, heuristic);
+		#[cfg(not(all(feature = "parallel", not(target_arch = "wasm32"))))]
+		let scores = score_moves(board, strength.depth, heuristic);
+		scores
+	})
+}
+
+/// `ai_next_move` with the column scores from `scores`, which is only called
+/// when the AI does not blunder, so scores searched ahead of time can be used
+pub fn reply<R: Rng>(board: Connect4, strength: Strength, rng: &mut R, scores: impl FnOnce() -> Vec<(usize, i32)>) -> Option<usize> 
+{
+	if board.termination {
+		return None;
+	}
+	if strength.blunders(rng) {
+		return random_move(board, rng);
+	}
+
+	let scores = scores();
+	let values: Vec<i32> = scores.iter().map(|(_, value)| *value).collect();
+
+	strength.choose(&values, rng).map(|index| scores[index].0)
+}
+
+/// Whether the AI could have played `col`, drawing from `rng` exactly what
+/// `ai_next_move` draws so the rest of the game replays; a column within the
+/// strength's temperature band of the best counts, not only the one it picked
+pub fn could_play<R: Rng>(board: Connect4, strength: Strength, heuristic: Heuristic, rng: &mut R, col: usize) -> bool 
+{
+	if board.termination {
+		return false;
+	}
+	if strength.blunders(rng) {
+		random_move(board, rng);
+		return board.get_availiable_columns().contains(&col);
+	}
+
+	let scores = score_moves(board, strength.depth, heuristic);
+	let values: Vec<i32> = scores.iter().map(|(_, value)| *value).collect();
+	strength.choose(&values, rng);
+
+	match scores.iter().position(|(scored, _)| *scored == col) {
+		Some(index) => strength.within_band(&values, index),
+		None => false,
+	}
+}
+
+/// Any playable column, `None` once the game is over
+pub fn random_move<R: Rng>(board: Connect4, rng: &mut R) -> Option<usize> {
+	if board.termination {
+		return None;
+	}
+	board.get_availiable_columns().choose(rng).copied()
+}
+
+/// Breaks ties between inner moves of `score_moves`, which only keeps their values
+fn tie_breaker() -> StdRng 
+{
+	StdRng::seed_from_u64(0)
+}
+
+/// Minimax value of every playable column for the player to move. The values
+/// draw nothing from the game's generator, so they can be searched ahead of time
+pub fn score_moves(board: Connect4, depth: usize, heuristic: Heuristic) -> Vec<(usize, i32)> 
+{
+	let mut rng = tie_breaker();
+
+	board.get_availiable_columns().into_iter().map(|col| {
+		let mut copy_board = board;
+		copy_board.place(col);
+		(col, find_best_move(copy_board, depth - 1, false, board.current_player, heuristic, &mut rng).1)
+	}).collect()
+}
+
+/// Minimax values already searched, kept from one search to the next. The minimax
+/// has no cut-offs, so a stored value is exactly what searching the position to
+/// the same depth again would give
+pub struct ScoreTable 
+{
+	heuristic: Heuristic,
+	/// Value by both players' pieces, the depth left and whether the AI is P1
+	values: HashMap<(u64, u64, u8, bool), i32>,
+}
+
+impl ScoreTable 
+{
+	/// Most values kept before the table starts over, about 24 MB
+	const CAPACITY: usize = 1 << 20;
+
+	pub fn new(heuristic: Heuristic) -> Self 
+	{
+		ScoreTable { heuristic, values: HashMap::new() }
+	}
+
+	pub fn len(&self) -> usize 
+	{
+		self.values.len()
+	}
+
+	pub fn is_empty(&self) -> bool 
+	{
+		self.values.is_empty()
+	}
+
+	fn key(board: &Connect4, depth: usize, cpu: C4Piece) -> (u64, u64, u8, bool) 
+	{
+		let mut pieces = (0, 0);
+		for (index, cell) in board.board.iter().flatten().enumerate() 
+		{
+			match cell 
+			{
+				Some(C4Piece::P1) => pieces.0 |= 1 << index,
+				Some(C4Piece::P2) => pieces.1 |= 1 << index,
+				None => {}
+			}
+		}
+		(pieces.0, pieces.1, depth as u8, cpu == C4Piece::P1)
+	}
+}
+
+/// `score_moves` drawing on and filling `table`; the values are the same, only
+/// positions it has seen before are not searched again
+pub fn score_moves_cached(board: Connect4, depth: usize, heuristic: Heuristic, table: &mut ScoreTable) -> Vec<(usize, i32)> 
+{
+	if table.heuristic != heuristic 
+	{
+		*table = ScoreTable::new(heuristic);
+	}
+
+	board.get_availiable_columns().into_iter().map(|col| {
+		let mut copy_board = board;
+		copy_board.place(col);
+		(col, cached_value(copy_board, depth - 1, false, board.current_player, table))
+	}).collect()
+}
+
+/// Value `find_best_move` gives `board`, looked up in `table` or searched and stored there
+fn cached_value(board: Connect4, depth: usize, is_cpu_turn: bool, cpu: C4Piece, table: &mut ScoreTable) -> i32 
+{
+	#[cfg(feature = "node-count")]
+	NODES.fetch_add(1, Ordering::Relaxed);
+
+	if board.termination || depth == 0 
+	{
+		return leaf_value(board, depth, cpu, table.heuristic);
+	}
+
+	let key = ScoreTable::key(&board, depth, cpu);
+	if let Some(value) = table.values.get(&key) 
+	{
+		return *value;
+	}
+
+	let values = board.get_columns().into_iter().filter_map(|col| {
+		let mut copy_board = board;
+		if !copy_board.place(col) 
+		{
+			return None;
+		}
+		Some(cached_value(copy_board, depth - 1, !is_cpu_turn, cpu, table))
+	});
+	let value = if is_cpu_turn { values.max().unwrap_or(i32::MIN) } else { values.min().unwrap_or(i32::MAX) };
+
+	if table.values.len() >= ScoreTable::CAPACITY 
+	{
+		table.values.clear();
+	}
+	table.values.insert(key, value);
+	value
+}
+
+/// `score_moves` with every column searched on its own thread
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+pub fn score_moves_parallel(board: Connect4, depth: usize, heuristic: Heuristic) -> Vec<(usize, i32)> 
+{
+	std::thread::scope(|scope| {
+		let searches: Vec<_> = board.get_availiable_columns().into_iter().map(|col| {
+			scope.spawn(move || {
+				let mut copy_board = board;
+				copy_board.place(col);
+				(col, find_best_move(copy_board, depth - 1, false, board.current_player, heuristic, &mut tie_breaker()).1)
+			})
+		}).collect();
+
+		searches.into_iter().map(|search| search.join().expect("Search thread panicked")).collect()
+	})
+}
+
+/// Evaluates every playable column for the player to move, searching as deep as `limit` allows
+pub fn analyze(board: Connect4, limit: Limit, heuristic: Heuristic) -> Analysis<usize> 
+{
+	let plies_left = if board.termination { 0 } else { NUM_ROWS * NUM_COLS - board.next_step };
+
+	deepen(limit, plies_left, |depth, deadline| {
+		board.get_availiable_columns().into_iter().map(|col| {
+			let mut copy_board = board;
+			copy_board.place(col);
+
+			let (score, mut pv) = principal_variation(copy_board, depth - 1, i32::MIN, i32::MAX, board.current_player, heuristic, deadline)?;
+			pv.insert(0, col);
+			Some(MoveEval::new(col, score, pv))
+		}).collect()
+	})
+}
+
+/// Replays a finished game from the empty board and judges every move
+pub fn review(moves: &[usize], limit: Limit, heuristic: Heuristic) -> Review<usize> 
+{
+	let mut board = Connect4::initialize();
+	let mut reviews = Vec::new();
+
+	for col in moves.iter() 
+	{
+		reviews.push(judge(*col, &analyze(board, limit, heuristic), EVALUATION_SCALE));
+		board.place(*col);
+	}
+
+	Review::new(reviews)
+}
+
+/// Alpha-beta version of `find_best_move` that also returns the expected
+/// continuation, or `None` once `deadline` has passed
+fn principal_variation(board: Connect4, depth: usize, mut alpha: i32, mut beta: i32, cpu: C4Piece, heuristic: Heuristic, deadline: &Deadline) -> Option<(i32, Vec<usize>)> 
+{
+	if board.termination || depth == 0 
+	{
+		return Some((leaf_value(board, depth, cpu, heuristic), Vec::new()));
+	} 
+	else if deadline.passed() 
+	{
+		return None;
+	}
+
+	let maximizing = board.current_player == cpu;
+	let mut best: Option<(i32, Vec<usize>)> = None;
+
+	for col in board.get_availiable_columns() 
+	{
+		let mut copy_board = board;
+		copy_board.place(col);
+
+		let (value, mut line) = principal_variation(copy_board, depth - 1, alpha, beta, cpu, heuristic, deadline)?;
+		let better = match &best 
+		{
+			None => true,
+			Some((best_value, _)) => if maximizing { value > *best_value } else { value < *best_value },
+		};
+		if better 
+		{
+			line.insert(0, col);
+			best = Some((value, line));
+		}
+
+		if maximizing { alpha = alpha.max(value); } else { beta = beta.min(value); }
+		if alpha >= beta 
+		{
+			break;
+		}
+	}
+
+	best
+}
+
+/// Value of a finished game or of the heuristic once the search stops
+fn leaf_value(board: Connect4, depth: usize, cpu: C4Piece, heuristic: Heuristic) -> i32 
+{
+	if !board.termination 
+	{
+		return match heuristic 
+		{
+			Heuristic::Classic(weights) => board.heuristic_searc_score(cpu, &weights),
+			Heuristic::Learned(model) => model.evaluate(&board, cpu),
+		};
+	}
+
+	match board.winner 
+	{
+		None => 0,
+		Some(color) => 
+		{
+			if color == cpu 
+			{
+				i32::MAX - 43 + depth as i32
+			} 
+			else 
+			{
+				i32::MIN + 43 - depth as i32
+			}
+		},
+	}
+}
+
+// Refer to
+// https://medium.com/analytics-vidhya/artificial-intelligence-at-play-connect-four-minimax-algorithm-explained-3b5fc32e4a4f
+fn find_best_move<R: Rng>(board: Connect4, depth: usize, is_cpu_turn: bool, cpu: C4Piece, heuristic: Heuristic, rng: &mut R) -> (usize, i32) 
+{
+	#[cfg(feature = "node-count")]
+	NODES.fetch_add(1, Ordering::Relaxed);
+
+	if board.termination || depth == 0 
+	{
+		return (3, leaf_value(board, depth, cpu, heuristic));
+	}
+
+	if is_cpu_turn 
+	{
+		let mut best_options = vec![(0, i32::MIN)];
+
+		for col in board.get_columns().iter() 
+		{
+			let mut copy_board = board;
+
+			if !copy_board.place(*col) 
+			{
+				continue;
+			}
+			let new_value = find_best_move(copy_board, depth - 1, false, cpu, heuristic, rng).1;
+
+			if new_value == best_options[0].1 
+			{
+				best_options.push((*col, new_value));
+			} 
+			else if new_value > best_options[0].1 
+			{
+				best_options = vec![(*col, new_value)];
+			}
+		}
+
+		*best_options.choose(rng).unwrap()
+	} 
+	else 
+	{
+		let mut best_options = vec![(0, i32::MAX)];
+
+		for col in board.get_columns().iter() 
+		{
+			let mut copy_board = board;
+			if !copy_board.place(*col) 
+			{
+				continue;
+			}
+
+			let new_value = find_best_move(copy_board, depth - 1, true, cpu, heuristic, rng).1;
+
+			if new_value == best_options[0].1 
+			{
+				best_options.push((*col, new_value));
+			} 
+			else if new_value < best_options[0].1 
+			{
+				best_options = vec![(*col, new_value)];
+			}
+		}
+		*best_options.choose(rng).unwrap()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn played(moves: &[usize]) -> Connect4 {
+		let mut board = Connect4::initialize();
+		for col in moves {
+			assert!(board.place(*col));
+		}
+		board
+	}
+
+	#[test]
+	fn cached_scores_match_the_plain_search() {
+		let mut table = ScoreTable::new(classic());
+		for moves in [&[][..], &[3, 3, 2], &[3, 4, 3, 4, 3], &[0, 6, 1, 5, 2, 4]] {
+			let board = played(moves);
+			for depth in 1..=4 {
+				assert_eq!(score_moves_cached(board, depth, classic(), &mut table), score_moves(board, depth, classic()), "after {:?} at depth {}", moves, depth);
+			}
+		}
+		assert!(!table.is_empty());
+	}
+
+	#[test]
+	fn starts_over_for_another_heuristic() {
+		let board = played(&[3, 3]);
+		let mut table = ScoreTable::new(classic());
+		score_moves_cached(board, 3, classic(), &mut table);
+
+		assert_eq!(score_moves_cached(board, 3, learned(), &mut table), score_moves(board, 3, learned()));
+		assert_eq!(table.heuristic, learned());
+	}
+}
@@ -0,0 +1,144 @@
+// Learned static evaluation: a logistic model over board features, trained by `game_test`
+use super::connect4::{Connect4, C4Piece, NUM_COLS, NUM_ROWS};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Threat and line counts, whose turn it is, then one square feature per cell
+pub const NUM_FEATURES: usize = 9 + NUM_ROWS * NUM_COLS;
+
+/// Search score of one logit; it equals `con4_ai::EVALUATION_SCALE`, so the
+/// evaluation bar shows the model's win probability
+pub const LOGIT_SCALE: f64 = 100.0;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LinearModel
+{
+	pub bias: f64,
+	pub weights: Vec<f64>,
+}
+
+/// The model shipped with the client, parsed on first use
+pub fn learned() -> &'static LinearModel
+{
+	static MODEL: OnceLock<LinearModel> = OnceLock::new();
+	MODEL.get_or_init(|| serde_json::from_str(include_str!("con4_model.json")).expect("Error parsing con4_model.json"))
+}
+
+/// Features of `board` from the side of `color`; swapping the colour mirrors
+/// them, so one set of weights serves both players
+pub fn features(board: &Connect4, color: C4Piece) -> Vec<f64>
+{
+	// Own and opposing threats on odd and even rows (counted from the bottom),
+	// threats that can be played right now, and open twos
+	let mut counts = [0.0; 8];
+
+	let mut window = |cells: [[usize; 2]; 4]|
+	{
+		let mut mine = 0;
+		let mut theirs = 0;
+		let mut empty = None;
+
+		for [row, col] in cells.iter()
+		{
+			match board.board[*row][*col]
+			{
+				Some(piece) if piece == color => mine += 1,
+				Some(_) => theirs += 1,
+				None => empty = Some([*row, *col]),
+			}
+		}
+
+		if mine + theirs == 4
+		{
+			return;
+		}
+		let side = match (mine, theirs)
+		{
+			(_, 0) if mine >= 2 => 0,
+			(0, _) if theirs >= 2 => 1,
+			_ => return,
+		};
+		if mine + theirs == 2
+		{
+			counts[6 + side] += 1.0;
+			return;
+		}
+
+		let [row, col] = empty.unwrap();
+		let height = NUM_ROWS - row;
+		counts[side * 2 + height % 2] += 1.0;
+		if board.col_row_index[col] == height - 1
+		{
+			counts[4 + side] += 1.0;
+		}
+	};
+
+	for row in 0..NUM_ROWS
+	{
+		for col in 0..NUM_COLS
+		{
+			if col + 3 < NUM_COLS
+			{
+				window([[row, col], [row, col + 1], [row, col + 2], [row, col + 3]]);
+			}
+			if row + 3 < NUM_ROWS
+			{
+				window([[row, col], [row + 1, col], [row + 2, col], [row + 3, col]]);
+			}
+			if row + 3 < NUM_ROWS && col + 3 < NUM_COLS
+			{
+				window([[row, col], [row + 1, col + 1], [row + 2, col + 2], [row + 3, col + 3]]);
+			}
+			if row >= 3 && col + 3 < NUM_COLS
+			{
+				window([[row, col], [row - 1, col + 1], [row - 2, col + 2], [row - 3, col + 3]]);
+			}
+		}
+	}
+
+	let mut features = counts.to_vec();
+	features.push(if board.current_player == color { 1.0 } else { -1.0 });
+	for row in 0..NUM_ROWS
+	{
+		for col in 0..NUM_COLS
+		{
+			features.push(match board.board[row][col]
+			{
+				Some(piece) if piece == color => 1.0,
+				Some(_) => -1.0,
+				None => 0.0,
+			});
+		}
+	}
+
+	features
+}
+
+impl Default for LinearModel
+{
+	fn default() -> Self
+	{
+		Self::new()
+	}
+}
+
+impl LinearModel
+{
+	/// An untrained model that scores every position as even
+	pub fn new() -> Self
+	{
+		LinearModel { bias: 0.0, weights: vec![0.0; NUM_FEATURES] }
+	}
+
+	/// Log-odds that `color` wins
+	pub fn logit(&self, features: &[f64]) -> f64
+	{
+		self.bias + self.weights.iter().zip(features.iter()).map(|(weight, feature)| weight * feature).sum::<f64>()
+	}
+
+	/// Leaf score for the search, on the scale of `LOGIT_SCALE`
+	pub fn evaluate(&self, board: &Connect4, color: C4Piece) -> i32
+	{
+		(self.logit(&features(board, color)) * LOGIT_SCALE).round() as i32
+	}
+}
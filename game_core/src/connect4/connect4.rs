@@ -1,4 +1,5 @@
 use std::fmt::{Display, Formatter, Result};
+use crate::types::eval_weights::EvalWeights;
 
 use C4Piece::*;
 
@@ -24,8 +25,8 @@ impl Display for C4Piece {
 	/// Prints out the piece color
 	fn fmt(&self, f: &mut Formatter) -> Result {
 		match self {
-			C4Piece::P1 => write!(f, "{}", "Red"),
-			C4Piece::P2 => write!(f, "{}", "Yellow"),
+			C4Piece::P1 => write!(f, "Red"),
+			C4Piece::P2 => write!(f, "Yellow"),
 		}
 	}
 }
@@ -75,7 +76,7 @@ impl Connect4 {
 
 	pub fn get_columns(&self) -> [usize; NUM_COLS] 
 	{
-		return [3, 2, 4, 1, 5, 0, 6];
+		[3, 2, 4, 1, 5, 0, 6]
 	}
 
 	pub fn get_availiable_columns(&self) -> Vec<usize> {
@@ -101,7 +102,7 @@ impl Connect4 {
 
 		if height == NUM_ROWS 
 		{
-			return false;
+			false
 		}
 		else
 		{
@@ -117,13 +118,13 @@ impl Connect4 {
 				None => None,
 			};
 
-			self.termination = self.winner != None || self.next_step == NUM_COLS * NUM_ROWS;
+			self.termination = self.winner.is_some() || self.next_step == NUM_COLS * NUM_ROWS;
 
 			self.col_row_index[col] += 1;
 
 			self.current_player = self.current_player.switch();
 
-			return true;
+			true
 		}
 	}
 
@@ -139,7 +140,7 @@ impl Connect4 {
 					None => return false,
 				}
 			}
-			return true;
+			true
 		};
 
 		for row_index in 0..NUM_ROWS 
@@ -149,7 +150,7 @@ impl Connect4 {
 				let partition = &self.board[row_index][st_index..st_index + 4];
 				if part_checking(partition) 
 				{
-					return Some((0..4).into_iter().map(|i| [row_index, st_index + i]).collect());
+					return Some((0..4).map(|i| [row_index, st_index + i]).collect());
 				}
 			}
 		}
@@ -159,10 +160,10 @@ impl Connect4 {
 			for st_index in 0..NUM_ROWS - 3 
 			{
 				let mut partition = vec![];
-				(st_index..st_index + 4).into_iter().for_each(|row_index| partition.push(self.board[row_index][col_index]));
+				(st_index..st_index + 4).for_each(|row_index| partition.push(self.board[row_index][col_index]));
 				if part_checking(&partition) 
 				{
-					return Some((0..4).into_iter().map(|i| [st_index + i, col_index]).collect());
+					return Some((0..4).map(|i| [st_index + i, col_index]).collect());
 				}
 			}
 		}
@@ -172,10 +173,10 @@ impl Connect4 {
 			for col_index in 0..NUM_COLS - 3 
 			{
 				let mut partition = vec![];
-				(0..4).into_iter().for_each(|i| partition.push(self.board[row_index - i][col_index + i]));
+				(0..4).for_each(|i| partition.push(self.board[row_index - i][col_index + i]));
 				if part_checking(&partition) 
 				{
-					return Some((0..4).into_iter().map(|i| [row_index - i, col_index + i]).collect());
+					return Some((0..4).map(|i| [row_index - i, col_index + i]).collect());
 				}
 			}
 		}
@@ -183,19 +184,19 @@ impl Connect4 {
 		for row_index in 0..NUM_ROWS - 3 {
 			for col_index in 0..NUM_COLS - 3 {
 				let mut window = vec![];
-				(0..4).into_iter().for_each(|i| window.push(self.board[row_index + i][col_index + i]));
+				(0..4).for_each(|i| window.push(self.board[row_index + i][col_index + i]));
 
 				if part_checking(&window) 
 				{
-					return Some((0..4).into_iter().map(|i| [row_index + i, col_index + i]).collect());
+					return Some((0..4).map(|i| [row_index + i, col_index + i]).collect());
 				}
 			}
 		}
 		
-		return None;
+		None
 	}
 
-	pub fn heuristic_searc_score(&self, color: C4Piece) -> i32 
+	pub fn heuristic_searc_score(&self, color: C4Piece, weights: &EvalWeights) -> i32 
 	{
 		let mut score = 0;
 
@@ -231,30 +232,30 @@ impl Connect4 {
 
 			if mine_chess > 0 && op_chess > 0 
 			{
-				return 0;
+				0
 			}
 			else{
 
 				if mine_chess == 4{
-					return 1000000;
+					weights.win
 				}
 				else if mine_chess == 3{
-					return 50;
+					weights.three
 				}
 				else if mine_chess == 2{
-					return 2;
+					weights.two
 				}
 				else if op_chess == 3 && empty == 1 && mine_chess == 0{
-					return -1000000;
+					weights.opponent_open_three
 				}
 				else if op_chess == 3 && below == 1 && mine_chess == 0{
-					return -100;
+					weights.opponent_three
 				}
 				else if op_chess == 2 && mine_chess == 0{
-					return -10;
+					weights.opponent_two
 				}
 				else{
-					return 0;
+					0
 				}
 			}
 		};
@@ -264,7 +265,7 @@ impl Connect4 {
 			for st_index in 0..NUM_COLS - 3 
 			{
 				let mut window: Vec<(BoardCell, bool)> = vec![];
-				(st_index..st_index + 4).into_iter().for_each(|col| {window.push((
+				(st_index..st_index + 4).for_each(|col| {window.push((
 					self.board[row_index][col],
 					self.col_row_index[col] >= NUM_ROWS - row_index - 1,))
 				});
@@ -277,7 +278,7 @@ impl Connect4 {
 			for st_index in 0..NUM_ROWS - 3 
 			{
 				let mut window: Vec<(BoardCell, bool)> = vec![];
-				(st_index..st_index + 4).into_iter().for_each(|row| window.push((self.board[row][col_index], true)));
+				(st_index..st_index + 4).for_each(|row| window.push((self.board[row][col_index], true)));
 				score += partition_score(&window);
 			}
 		}
@@ -287,7 +288,7 @@ impl Connect4 {
 			for col_index in 0..NUM_COLS - 3 
 			{
 				let mut window: Vec<(BoardCell, bool)> = vec![];
-				(0..4).into_iter().for_each(|i| {window.push((
+				(0..4).for_each(|i| {window.push((
 					self.board[row_index - i][col_index + i],
 					self.col_row_index[col_index + i] >= NUM_ROWS - (row_index - i) - 1,))
 				});
@@ -300,7 +301,7 @@ impl Connect4 {
 			for col_index in 0..NUM_COLS - 3 
 			{
 				let mut window = vec![];
-				(0..4).into_iter().for_each(|i| {window.push((
+				(0..4).for_each(|i| {window.push((
 					self.board[row_index + i][col_index + i],
 					self.col_row_index[col_index + i] >= NUM_ROWS - (row_index + i) - 1,))
 				});
@@ -311,19 +312,13 @@ impl Connect4 {
 
 		for row_index in 0..NUM_ROWS 
 		{
-			match self.board[row_index][3] {
-				Some(own_color) =>
-				{
-					if own_color == color 
-					{
-						score += 10;
-					}
-				},
-				None => {}
+			if self.board[row_index][3] == Some(color) 
+			{
+				score += weights.center;
 			}
 		}
 
-		return score;
+		score
 	}
 }
 
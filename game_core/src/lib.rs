@@ -0,0 +1,33 @@
+// Connect 4 and TOOT and OTTO: the rules, the AIs and the types they share.
+// Nothing here touches the browser, so the client, the server and game_test all build on it
+
+#[cfg(not(any(feature = "native", feature = "wasm")))]
+compile_error!("game_core needs either the `native` or the `wasm` feature");
+
+#[cfg(all(feature = "native", feature = "wasm"))]
+compile_error!("game_core takes only one of the `native` and `wasm` features; turn off the default features for `wasm`");
+
+// Each game's module holds a module of the same name with its rules
+#[allow(clippy::module_inception)]
+pub mod connect4 {
+	pub mod connect4;
+	pub mod con4_ai;
+	pub mod con4_model;
+}
+
+#[allow(clippy::module_inception)]
+pub mod toot_and_otto {
+	pub mod toot_ai;
+	pub mod toot_and_otto;
+	pub mod toot_solver;
+}
+
+pub mod online;
+pub mod record;
+
+pub mod types {
+	pub mod analysis;
+	pub mod eval_weights;
+	pub mod review;
+	pub mod strength;
+}
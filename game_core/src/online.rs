@@ -0,0 +1,156 @@
+// Live games between two players over the server's WebSocket: the messages both
+// ends send as JSON text frames, and the position the server plays every move on
+use crate::connect4::connect4::{Connect4, C4Piece};
+use crate::record::{self, CONNECT4, LOST, TIE, TOOT, WON};
+use crate::toot_and_otto::toot_and_otto::{TootAndOtto, Player};
+use serde::{Deserialize, Serialize};
+
+/// What a client sends
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+	/// The first message on a connection: the session token from logging in
+	Hello { token: String },
+	/// Join the matchmaking queue for a game of this mode, `record::CONNECT4` or `record::TOOT`
+	Seek { mode: u8 },
+	/// Leave the queue, or close the room the player opened
+	Cancel,
+	/// Open a private room for a game of this mode, which a friend joins with its code
+	CreateRoom { mode: u8 },
+	/// Take the second seat of a room; its host gets the room back instead
+	JoinRoom { code: String },
+	/// A move in the engine protocol's notation
+	Move { mv: String },
+	Resign,
+}
+
+/// What the server sends
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+	/// The connection is logged in as this user
+	Welcome { username: String },
+	/// In the queue, still waiting for an opponent; sent on joining and then every second
+	Queued(QueueStatus),
+	/// A room is open under this code until someone joins it or it expires
+	Room { code: String, mode: u8 },
+	/// A game started, or the game the user was in when they reconnected
+	Game(LiveState),
+	/// A move was played by either side; `time_left` is what the player now to move has
+	Moved { mv: String, time_left: u64 },
+	/// The opponent lost their connection and forfeits unless back within `time_left`
+	OpponentAway { time_left: u64 },
+	OpponentBack,
+	/// The game ended; `result` is from the side of the first player, like a game record's
+	Over { result: u8, reason: EndReason },
+	Error { message: String },
+}
+
+/// A game as it stands, for a player joining or rejoining it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LiveState {
+	/// Id of the game, which the games collection keeps once it is over
+	pub id: String,
+	pub mode: u8,
+	/// First player (P1, TOOT) first
+	pub players: [String; 2],
+	/// The players' ratings for this game as it started
+	pub ratings: [i32; 2],
+	/// Whether the result moves the ratings; games in private rooms do not
+	pub rated: bool,
+	/// Which of `players` the receiver is
+	pub seat: usize,
+	pub moves: Vec<String>,
+	/// Milliseconds the player to move has left for the move
+	pub time_left: u64,
+}
+
+/// A player's place in the matchmaking queue
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QueueStatus {
+	pub mode: u8,
+	/// The player's rating for this game
+	pub rating: i32,
+	/// How far from `rating` an opponent may be; it widens the longer the player waits
+	pub range: i32,
+	/// Milliseconds in the queue so far
+	pub waiting: u64,
+	/// Players in the queue for this game, the receiver included
+	pub queued: usize,
+}
+
+/// How a live game ended
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EndReason {
+	/// Played to a win or a tie
+	Finished,
+	Resigned,
+	/// The player to move ran out of time
+	TimedOut,
+	/// A player stayed disconnected too long
+	Abandoned,
+}
+
+/// Result code of a game the player in `seat` lost
+pub fn loss_of(seat: usize) -> u8 {
+	if seat == 0 { LOST } else { WON }
+}
+
+/// The position of a live game
+#[derive(Copy, Clone)]
+pub enum Position {
+	Connect4(Connect4),
+	Toot(TootAndOtto),
+}
+
+impl Position {
+	/// The empty board of a game mode
+	pub fn new(mode: u8) -> Option<Position> {
+		match mode {
+			CONNECT4 => Some(Position::Connect4(Connect4::initialize())),
+			TOOT => Some(Position::Toot(TootAndOtto::new())),
+			_ => None,
+		}
+	}
+
+	/// Which seat is to move: 0 for the first player, 1 for the second
+	pub fn to_move(&self) -> usize {
+		match self {
+			Position::Connect4(board) => (board.current_player == C4Piece::P2) as usize,
+			Position::Toot(board) => (board.current_player == Player::OTTO) as usize,
+		}
+	}
+
+	/// Plays a move given in notation; false when it cannot be read, is illegal or the game is over
+	pub fn play(&mut self, mv: &str) -> bool {
+		match self {
+			Position::Connect4(board) => match record::parse_connect4(mv) {
+				Some(col) if !board.termination => board.place(col),
+				_ => false,
+			},
+			Position::Toot(board) => match record::parse_toot(mv) {
+				Some((col, letter)) if !board.termination => board.drop(letter, col),
+				_ => false,
+			},
+		}
+	}
+
+	/// Result code once the game is over, `None` while it goes on
+	pub fn result(&self) -> Option<u8> {
+		match self {
+			Position::Connect4(board) => match (board.termination, board.winner) {
+				(false, _) => None,
+				(true, Some(C4Piece::P1)) => Some(WON),
+				(true, Some(C4Piece::P2)) => Some(LOST),
+				(true, None) => Some(TIE),
+			},
+			Position::Toot(board) => match (board.termination, board.winner) {
+				(false, _) => None,
+				(true, Some(Player::TOOT)) => Some(WON),
+				(true, Some(Player::OTTO)) => Some(LOST),
+				(true, None) => Some(TIE),
+			},
+		}
+	}
+}
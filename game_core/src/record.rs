@@ -0,0 +1,343 @@
+// Finished games as the client submits them, and their replay through the
+// engines, so the server can check a result instead of taking it on trust
+use crate::connect4::{con4_ai, connect4::{Connect4, C4Piece}};
+use crate::toot_and_otto::{toot_ai, toot_and_otto::{TootAndOtto, Player, TOenum}};
+use crate::types::strength::Strength;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result};
+
+/// `mode` of a Connect 4 game
+pub const CONNECT4: u8 = 0;
+/// `mode` of a TOOT and OTTO game
+pub const TOOT: u8 = 1;
+
+/// Result codes, from the side of the first player (the human)
+pub const LOST: u8 = 0;
+pub const WON: u8 = 1;
+pub const TIE: u8 = 2;
+
+/// The computer opponent of a game
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ai {
+	/// `ai_next_move` at this (possibly fractional) level
+	Level(f64),
+	/// TOOT and OTTO's solver, `toot_ai::perfect_move`
+	Perfect,
+}
+
+/// Which static evaluation the Connect 4 AI searched with
+#[derive(Copy, Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Evaluator {
+	/// `con4_ai::classic`, and the only evaluation of TOOT and OTTO's AI
+	#[default]
+	Classic,
+	/// `con4_ai::learned`
+	Learned,
+}
+
+impl Evaluator {
+	pub fn heuristic(self) -> con4_ai::Heuristic {
+		match self {
+			Evaluator::Classic => con4_ai::classic(),
+			Evaluator::Learned => con4_ai::learned(),
+		}
+	}
+}
+
+/// A finished game against a human or the computer, who always moves second
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+	/// `CONNECT4` or `TOOT`
+	pub mode: u8,
+	/// Every move in the engine protocol's notation
+	pub moves: Vec<String>,
+	/// `None` when two humans shared the board
+	pub ai: Option<Ai>,
+	/// Seed of the generator the AI drew its random choices from
+	pub seed: u64,
+	/// What the Connect 4 AI evaluated positions with, classic when left out
+	#[serde(default)]
+	pub evaluator: Evaluator,
+}
+
+/// A game against the computer as the server started it: the seed its AI draws
+/// from, and the id the finished game is sent back under so that it can score
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StartedGame {
+	pub id: String,
+	pub seed: u64,
+}
+
+/// Why a record was turned down
+#[derive(Clone, Debug, PartialEq)]
+pub enum Rejection {
+	UnknownMode(u8),
+	/// The move at this index cannot be read
+	BadNotation(usize),
+	/// The move at this index is not legal in its position
+	IllegalMove(usize),
+	/// The game goes on after this many moves
+	PlayedAfterEnd(usize),
+	Unfinished,
+	/// The AI would not have played the move at this index
+	NotTheAi(usize),
+	/// The AI cannot play this game
+	UnknownAi,
+}
+
+impl Display for Rejection {
+	fn fmt(&self, f: &mut Formatter) -> Result {
+		match self {
+			Rejection::UnknownMode(mode) => write!(f, "unknown game mode {}", mode),
+			Rejection::BadNotation(index) => write!(f, "move {} cannot be read", index + 1),
+			Rejection::IllegalMove(index) => write!(f, "move {} is illegal", index + 1),
+			Rejection::PlayedAfterEnd(plies) => write!(f, "moves were played after the game ended on move {}", plies),
+			Rejection::Unfinished => write!(f, "the game is not over"),
+			Rejection::NotTheAi(index) => write!(f, "move {} is not the computer's", index + 1),
+			Rejection::UnknownAi => write!(f, "no such computer opponent for this game"),
+		}
+	}
+}
+
+pub fn connect4_notation(col: usize) -> String {
+	col.to_string()
+}
+
+pub fn parse_connect4(text: &str) -> Option<usize> {
+	text.parse().ok()
+}
+
+pub fn toot_notation((col, letter): (usize, TOenum)) -> String {
+	format!("{}{}", letter, col)
+}
+
+pub fn parse_toot(text: &str) -> Option<(usize, TOenum)> {
+	let letter = match text.chars().next()? {
+		'T' | 't' => TOenum::T,
+		'O' | 'o' => TOenum::O,
+		_ => return None,
+	};
+
+	Some((text[1..].parse().ok()?, letter))
+}
+
+impl GameRecord {
+	/// What tells this game apart from any other: a game submitted twice has the same key
+	pub fn key(&self) -> String {
+		let ai = match self.ai {
+			Some(Ai::Level(level)) => level.to_string(),
+			Some(Ai::Perfect) => String::from("perfect"),
+			None => String::from("human"),
+		};
+		format!("{}:{}:{}:{:?}:{}", self.mode, self.seed, ai, self.evaluator, self.moves.join(","))
+	}
+
+	/// Replays the game from the empty board and returns its result code. Every
+	/// move must be legal, the game must end on the last one, and every computer
+	/// move must be one the AI could play when it draws from a generator seeded
+	/// with `seed`, as the game pages do
+	pub fn verify(&self) -> std::result::Result<u8, Rejection> {
+		match self.mode {
+			CONNECT4 => self.verify_connect4(),
+			TOOT => self.verify_toot(),
+			mode => Err(Rejection::UnknownMode(mode)),
+		}
+	}
+
+	/// Whether the move at `index` was the computer's
+	fn computer_moves(&self, index: usize) -> bool {
+		self.ai.is_some() && index % 2 == 1
+	}
+
+	fn verify_connect4(&self) -> std::result::Result<u8, Rejection> {
+		let strength = match self.ai {
+			Some(Ai::Level(level)) => Some(Strength::from_level(level)),
+			Some(Ai::Perfect) => return Err(Rejection::UnknownAi),
+			None => None,
+		};
+		let heuristic = self.evaluator.heuristic();
+		let mut rng = StdRng::seed_from_u64(self.seed);
+		let mut board = Connect4::initialize();
+
+		for (index, text) in self.moves.iter().enumerate() {
+			if board.termination {
+				return Err(Rejection::PlayedAfterEnd(index));
+			}
+			let col = parse_connect4(text).ok_or(Rejection::BadNotation(index))?;
+
+			if let (true, Some(strength)) = (self.computer_moves(index), strength) {
+				if !con4_ai::could_play(board, strength, heuristic, &mut rng, col) {
+					return Err(Rejection::NotTheAi(index));
+				}
+			}
+			if !board.place(col) {
+				return Err(Rejection::IllegalMove(index));
+			}
+		}
+
+		match (board.termination, board.winner) {
+			(false, _) => Err(Rejection::Unfinished),
+			(true, Some(C4Piece::P1)) => Ok(WON),
+			(true, Some(C4Piece::P2)) => Ok(LOST),
+			(true, None) => Ok(TIE),
+		}
+	}
+
+	fn verify_toot(&self) -> std::result::Result<u8, Rejection> {
+		if self.evaluator != Evaluator::Classic {
+			return Err(Rejection::UnknownAi);
+		}
+		let mut rng = StdRng::seed_from_u64(self.seed);
+		let mut board = TootAndOtto::new();
+
+		for (index, text) in self.moves.iter().enumerate() {
+			if board.termination {
+				return Err(Rejection::PlayedAfterEnd(index));
+			}
+			let (col, letter) = parse_toot(text).ok_or(Rejection::BadNotation(index))?;
+
+			if self.computer_moves(index) {
+				let possible = match self.ai {
+					Some(Ai::Level(level)) => toot_ai::could_play(board, Strength::from_level(level), &mut rng, (col, letter)),
+					_ => toot_ai::perfect_move(board) == (col, letter),
+				};
+				if !possible {
+					return Err(Rejection::NotTheAi(index));
+				}
+			}
+			if !board.drop(letter, col) {
+				return Err(Rejection::IllegalMove(index));
+			}
+		}
+
+		match (board.termination, board.winner) {
+			(false, _) => Err(Rejection::Unfinished),
+			(true, Some(Player::TOOT)) => Ok(WON),
+			(true, Some(Player::OTTO)) => Ok(LOST),
+			(true, None) => Ok(TIE),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::types::strength::MAX_LEVEL;
+
+	/// A Connect 4 game against the computer at `level`, the human always playing
+	/// the leftmost open column, as the page would send it
+	fn connect4_game(level: f64, seed: u64) -> (GameRecord, u8) {
+		let mut rng = StdRng::seed_from_u64(seed);
+		let mut board = Connect4::initialize();
+		let mut moves = Vec::new();
+
+		while !board.termination {
+			let col = if moves.len() % 2 == 0 {
+				board.get_availiable_columns()[0]
+			} else {
+				con4_ai::ai_next_move(board, Strength::from_level(level), con4_ai::classic(), &mut rng).unwrap()
+			};
+			board.place(col);
+			moves.push(connect4_notation(col));
+		}
+
+		let result = match board.winner {
+			Some(C4Piece::P1) => WON,
+			Some(C4Piece::P2) => LOST,
+			None => TIE,
+		};
+		(GameRecord { mode: CONNECT4, moves, ai: Some(Ai::Level(level)), seed, evaluator: Evaluator::Classic }, result)
+	}
+
+	#[test]
+	fn an_honest_game_verifies() {
+		for (level, seed) in [(1.0, 7), (4.5, 8), (MAX_LEVEL as f64, 9)] {
+			let (record, result) = connect4_game(level, seed);
+			assert_eq!(record.verify(), Ok(result));
+		}
+	}
+
+	#[test]
+	fn a_tampered_result_is_caught() {
+		let (mut record, result) = connect4_game(MAX_LEVEL as f64, 3);
+		assert_eq!(result, LOST);
+		assert_ne!(record.verify(), Ok(WON));
+
+		// Leaving out the computer's winning move does not turn the loss into anything else
+		record.moves.pop();
+		assert_eq!(record.verify(), Err(Rejection::Unfinished));
+	}
+
+	#[test]
+	fn an_illegal_move_is_caught() {
+		let (mut record, _) = connect4_game(1.0, 4);
+		record.moves[0] = String::from("9");
+		assert_eq!(record.verify(), Err(Rejection::IllegalMove(0)));
+
+		let record = GameRecord {
+			mode: CONNECT4,
+			moves: ["0", "0", "0", "0", "0", "0", "0"].iter().map(|col| col.to_string()).collect(),
+			ai: None,
+			seed: 0,
+			evaluator: Evaluator::Classic,
+		};
+		assert_eq!(record.verify(), Err(Rejection::IllegalMove(6)));
+	}
+
+	#[test]
+	fn a_substituted_ai_move_is_caught() {
+		let (mut record, _) = connect4_game(MAX_LEVEL as f64, 5);
+
+		// The strongest level has no temperature, so any column scored below the best was not its move
+		let mut board = Connect4::initialize();
+		board.place(parse_connect4(&record.moves[0]).unwrap());
+		let scores = con4_ai::score_moves(board, Strength::from_level(MAX_LEVEL as f64).depth, con4_ai::classic());
+		let best = scores.iter().map(|(_, score)| *score).max().unwrap();
+		let (worse, _) = scores.iter().find(|(_, score)| *score < best).unwrap();
+
+		record.moves[1] = connect4_notation(*worse);
+		assert_eq!(record.verify(), Err(Rejection::NotTheAi(1)));
+	}
+
+	#[test]
+	fn a_toot_game_verifies_and_keeps_its_ai() {
+		let level = 3.0;
+		let seed = 11;
+		let mut rng = StdRng::seed_from_u64(seed);
+		let mut board = TootAndOtto::new();
+		let mut moves = Vec::new();
+
+		while !board.termination {
+			let mv = if moves.len() % 2 == 0 {
+				board.legal_moves()[0]
+			} else {
+				toot_ai::ai_next_move(board, Strength::from_level(level), &mut rng).unwrap()
+			};
+			board.drop(mv.1, mv.0);
+			moves.push(toot_notation(mv));
+		}
+
+		let mut record = GameRecord { mode: TOOT, moves, ai: Some(Ai::Level(level)), seed, evaluator: Evaluator::Classic };
+		assert!(record.verify().is_ok());
+
+		record.evaluator = Evaluator::Learned;
+		assert_eq!(record.verify(), Err(Rejection::UnknownAi));
+	}
+
+	#[test]
+	fn keys_tell_games_apart() {
+		let (record, _) = connect4_game(2.0, 6);
+		assert_eq!(record.key(), record.clone().key());
+
+		let mut reseeded = record.clone();
+		reseeded.seed += 1;
+		assert_ne!(record.key(), reseeded.key());
+
+		let mut shorter = record.clone();
+		shorter.moves.pop();
+		assert_ne!(record.key(), shorter.key());
+	}
+}
@@ -0,0 +1,255 @@
+use super::{
+	toot_and_otto::{TootAndOtto, Player, TOenum, TOenum::*, NUM_COLS, NUM_ROWS},
+	toot_solver::{Book, Solver},
+};
+use crate::types::{
+	analysis::{deepen, Analysis, Deadline, Limit, MoveEval},
+	eval_weights::EvalWeights,
+	review::{judge, Review},
+	strength::Strength,
+};
+use rand::{seq::SliceRandom, Rng};
+use std::{cell::RefCell, cmp::Reverse};
+
+/// Solved values of the opening positions, written by the solver in `game_test`
+pub(crate) const BOOK: &[u8] = include_bytes!("toot_book.bin");
+
+/// Transposition table of the perfect player, 2^20 entries (8 MB)
+const PERFECT_TABLE_BITS: usize = 20;
+
+thread_local! {
+	/// The perfect player's solver, built on the first perfect move; its table stays
+	/// allocated and warm from one move, and one game, to the next
+	static SOLVER: RefCell<Solver> = RefCell::new(Solver::new(PERFECT_TABLE_BITS, Some(Book::from_bytes(BOOK))));
+}
+
+/// Deepest search the leveled AI runs
+pub const MAX_DEPTH: usize = 4;
+
+/// Approximate Elo of each level (index 0 is level 1), measured with the
+/// calibration mode of `game_test` and anchored so level 1 sits at 800
+pub const LEVEL_ELO: [i32; 10] = [800, 832, 909, 990, 1080, 1159, 1321, 1423, 1545, 1616];
+
+/// Inventory score that fills about 73% of an evaluation bar
+pub const EVALUATION_SCALE: f64 = 50.0;
+
+/// The shipped weights of both heuristics, written by the tuner of `game_test`
+pub fn weights() -> EvalWeights {
+	EvalWeights::from_json(include_str!("toot_weights.json")).expect("Error parsing toot_weights.json")
+}
+
+/// Which static evaluation the search uses at its leaves
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Heuristic {
+	/// The original window counting in `heuristic_searc_score`, with these weights
+	Classic(EvalWeights),
+	/// Inventory-aware threats from `inventory_search_score`, with these weights
+	Inventory(EvalWeights),
+}
+
+/// The inventory heuristic with the shipped weights, the one the AI plays
+pub fn inventory() -> Heuristic {
+	Heuristic::Inventory(weights())
+}
+
+/// Picks the AI's move, `None` once the game is over; every random choice is
+/// drawn from `rng`, so a seeded generator replays the same game
+pub fn ai_next_move<R: Rng>(board: TootAndOtto, strength: Strength, rng: &mut R) -> Option<(usize, TOenum)> {
+	if strength.blunders(rng) {
+		return random_move(board, rng);
+	}
+
+	// Twelve moves a ply instead of seven, so the search stops one ply earlier than Connect 4
+	let scores = score_moves(board, strength.depth.min(MAX_DEPTH), inventory(), rng);
+	let values: Vec<i32> = scores.iter().map(|(_, _, value)| *value).collect();
+	strength.choose(&values, rng).map(|index| (scores[index].0, scores[index].1))
+}
+
+/// Whether the AI could have played `mv`, drawing from `rng` exactly what
+/// `ai_next_move` draws so the rest of the game replays; a move within the
+/// strength's temperature band of the best counts, not only the one it picked
+pub fn could_play<R: Rng>(board: TootAndOtto, strength: Strength, rng: &mut R, mv: (usize, TOenum)) -> bool {
+	if strength.blunders(rng) {
+		random_move(board, rng);
+		return board.legal_moves().contains(&mv);
+	}
+
+	let scores = score_moves(board, strength.depth.min(MAX_DEPTH), inventory(), rng);
+	let values: Vec<i32> = scores.iter().map(|(_, _, value)| *value).collect();
+	strength.choose(&values, rng);
+
+	match scores.iter().position(|(col, letter, _)| (*col, *letter) == mv) {
+		Some(index) => strength.within_band(&values, index),
+		None => false,
+	}
+}
+
+/// Minimax value of every legal move for the player to move
+pub fn score_moves<R: Rng>(board: TootAndOtto, depth: usize, heuristic: Heuristic, rng: &mut R) -> Vec<(usize, TOenum, i32)> {
+	board.legal_moves().into_iter().map(|(col, letter)| {
+		let mut copy_board = board;
+		copy_board.drop(letter, col);
+		(col, letter, find_best_move(copy_board, depth - 1, false, board.current_player, heuristic, rng).2)
+	}).collect()
+}
+
+/// Any legal move, `None` once the game is over
+pub fn random_move<R: Rng>(board: TootAndOtto, rng: &mut R) -> Option<(usize, TOenum)> {
+	board.legal_moves().choose(rng).copied()
+}
+
+/// Plays a move that keeps the solved value of the position, preferring the
+/// one the inventory heuristic likes best when several do
+pub fn perfect_move(board: TootAndOtto) -> (usize, TOenum) {
+	SOLVER.with(|solver| keep_value(&mut solver.borrow_mut(), board))
+}
+
+fn keep_value(solver: &mut Solver, board: TootAndOtto) -> (usize, TOenum) {
+	let value = solver.solve(&board);
+	let weights = weights();
+
+	let mut candidates: Vec<(usize, TOenum, i32)> = board.legal_moves().into_iter().map(|(col, letter)| {
+		let mut copy_board = board;
+		copy_board.drop(letter, col);
+		(col, letter, copy_board.inventory_search_score(board.current_player, &weights))
+	}).collect();
+	candidates.sort_by_key(|candidate| Reverse(candidate.2));
+
+	for (col, letter, _) in candidates.iter() {
+		if solver.move_value(&board, *col, *letter) == value {
+			return (*col, *letter);
+		}
+	}
+
+	(candidates[0].0, candidates[0].1)
+}
+
+/// Evaluates every legal move for the player to move with the inventory
+/// heuristic, searching as deep as `limit` allows
+pub fn analyze(board: TootAndOtto, limit: Limit) -> Analysis<(usize, TOenum)> {
+	let plies_left = if board.termination { 0 } else { NUM_ROWS * NUM_COLS - board.next_step };
+	let heuristic = inventory();
+
+	deepen(limit, plies_left, |depth, deadline| {
+		board.legal_moves().into_iter().map(|(col, letter)| {
+			let mut copy_board = board;
+			copy_board.drop(letter, col);
+
+			let (score, mut pv) = principal_variation(copy_board, depth - 1, i32::MIN, i32::MAX, board.current_player, heuristic, deadline)?;
+			pv.insert(0, (col, letter));
+			Some(MoveEval::new((col, letter), score, pv))
+		}).collect()
+	})
+}
+
+/// Replays a finished game from the empty board and judges every move
+pub fn review(moves: &[(usize, TOenum)], limit: Limit) -> Review<(usize, TOenum)> {
+	let mut board = TootAndOtto::new();
+	let mut reviews = Vec::new();
+
+	for (col, letter) in moves.iter() {
+		reviews.push(judge((*col, *letter), &analyze(board, limit), EVALUATION_SCALE));
+		board.drop(*letter, *col);
+	}
+
+	Review::new(reviews)
+}
+
+/// Alpha-beta version of `find_best_move` that also returns the expected
+/// continuation, or `None` once `deadline` has passed
+fn principal_variation(board: TootAndOtto, depth: usize, mut alpha: i32, mut beta: i32, cpu: Player, heuristic: Heuristic, deadline: &Deadline) -> Option<(i32, Vec<(usize, TOenum)>)> {
+	if board.termination || depth == 0 {
+		return Some((leaf_value(board, depth, cpu, heuristic), Vec::new()));
+	} else if deadline.passed() {
+		return None;
+	}
+
+	let maximizing = board.current_player == cpu;
+	let mut best: Option<(i32, Vec<(usize, TOenum)>)> = None;
+
+	for (col, letter) in board.legal_moves() {
+		let mut copy_board = board;
+		copy_board.drop(letter, col);
+
+		let (value, mut line) = principal_variation(copy_board, depth - 1, alpha, beta, cpu, heuristic, deadline)?;
+		let better = match &best {
+			None => true,
+			Some((best_value, _)) => if maximizing { value > *best_value } else { value < *best_value },
+		};
+		if better {
+			line.insert(0, (col, letter));
+			best = Some((value, line));
+		}
+
+		if maximizing { alpha = alpha.max(value); } else { beta = beta.min(value); }
+		if alpha >= beta {
+			break;
+		}
+	}
+
+	best
+}
+
+/// Value of a finished game or of `heuristic` once the search stops
+fn leaf_value(board: TootAndOtto, depth: usize, cpu: Player, heuristic: Heuristic) -> i32 {
+	if !board.termination {
+		return match heuristic {
+			Heuristic::Classic(weights) => board.heuristic_searc_score(cpu, &weights),
+			Heuristic::Inventory(weights) => board.inventory_search_score(cpu, &weights),
+		};
+	}
+
+	match board.winner {
+		None => 0, // Draw
+		Some(player) => {
+			if player == cpu {
+				i32::MAX - 25 + depth as i32 // Computer won, good
+			} else {
+				i32::MIN + 25 - depth as i32 // Human won, bad
+			}
+		},
+	}
+}
+
+/// Minimax from the point of view of `cpu`, scoring leaves with `heuristic`
+pub fn find_best_move<R: Rng>(board: TootAndOtto, depth: usize, is_cpu_turn: bool, cpu: Player, heuristic: Heuristic, rng: &mut R) -> (usize, TOenum, i32) {
+	if board.termination || depth == 0 {
+		return (3, O, leaf_value(board, depth, cpu, heuristic));
+	}
+
+	if is_cpu_turn {
+		let mut best_options = vec![(0, T, i32::MIN)];
+
+		for (col, letter) in board.legal_moves() {
+			let mut copy_board = board;
+			copy_board.drop(letter, col);
+
+			let new_value = find_best_move(copy_board, depth - 1, false, cpu, heuristic, rng).2;
+
+			if new_value == best_options[0].2 {
+				best_options.push((col, letter, new_value));
+			} else if new_value > best_options[0].2 {
+				best_options = vec![(col, letter, new_value)];
+			}
+		}
+
+		*best_options.choose(rng).unwrap()
+	} else {
+		let mut best_options = vec![(0, T, i32::MAX)];
+
+		for (col, letter) in board.legal_moves() {
+			let mut copy_board = board;
+			copy_board.drop(letter, col);
+
+			let new_value = find_best_move(copy_board, depth - 1, true, cpu, heuristic, rng).2;
+
+			if new_value == best_options[0].2 {
+				best_options.push((col, letter, new_value));
+			} else if new_value < best_options[0].2 {
+				best_options = vec![(col, letter, new_value)];
+			}
+		}
+
+		*best_options.choose(rng).unwrap()
+	}
+}
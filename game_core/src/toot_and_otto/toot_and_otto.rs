@@ -1,5 +1,6 @@
 use std::fmt::{Display, Formatter, Result};
 use strum_macros::EnumIter;
+use crate::types::eval_weights::EvalWeights;
 use TOenum::*;
 use Player::*;
 
@@ -28,8 +29,8 @@ impl Player {
 impl Display for Player {
 	fn fmt(&self, f: &mut Formatter) -> Result {
 		match self {
-			TOOT => write!(f, "{}", "TOOT"),
-			OTTO => write!(f, "{}", "OTTO"),
+			TOOT => write!(f, "TOOT"),
+			OTTO => write!(f, "OTTO"),
 		}
 	}
 }
@@ -58,6 +59,14 @@ pub const NUM_ROWS: usize = 4;
 
 type BoardCell = Option<TOenum>;
 
+impl Default for TootAndOtto 
+{
+	fn default() -> Self 
+	{
+		Self::new()
+	}
+}
+
 impl TootAndOtto {
 	pub fn new() -> Self 
 	{
@@ -84,26 +93,62 @@ impl TootAndOtto {
 		[2, 3, 1, 4, 0, 5]
 	}
 
-	pub fn drop(&mut self, letter: TOenum, col: usize) -> bool 
+	/// Number of tiles of `letter` that `player` still has in hand
+	pub fn remaining(&self, player: Player, letter: TOenum) -> usize 
 	{
-		if col >= NUM_COLS 
+		let player_index = match player 
 		{
-			return false;
+			TOOT => 0,
+			OTTO => 1,
+		};
+
+		let letter_index = match letter 
+		{
+			T => 0,
+			O => 1,
+		};
+
+		self.countings[player_index][letter_index]
+	}
+
+	/// Every (column, letter) pair the current player can drop, given the
+	/// column heights and the letters left in their hand
+	pub fn legal_moves(&self) -> Vec<(usize, TOenum)> 
+	{
+		let mut moves = Vec::new();
+
+		if self.termination 
+		{
+			return moves;
 		}
 
-		let piece_count = match self.current_player 
+		for letter in [T, O] 
 		{
-			TOOT => match letter 
+			if self.remaining(self.current_player, letter) == 0 
 			{
-				T => self.countings[0][0],
-				O => self.countings[0][1],
-			},
-			OTTO => match letter 
+				continue;
+			}
+
+			for col in self.get_columns() 
 			{
-				T => self.countings[1][0],
-				O => self.countings[1][1],
-			},
-		};
+				if self.get_height(col) < NUM_ROWS 
+				{
+					moves.push((col, letter));
+				}
+			}
+		}
+
+		moves
+	}
+
+	pub fn drop(&mut self, letter: TOenum, col: usize) -> bool 
+	{
+		if col >= NUM_COLS 
+		{
+			return false;
+		}
+
+		let piece_count = self.remaining(self.current_player, letter);
 
 		if piece_count == 0 
 		{
@@ -122,10 +167,10 @@ impl TootAndOtto {
 
 		self.board[row][col] = Some(letter);
 
-		let winning_TOOT = self.check_win(TOOT);
-		let winning_OTTO = self.check_win(OTTO);
+		let winning_toot = self.check_win(TOOT);
+		let winning_otto = self.check_win(OTTO);
 
-		self.winner = match (winning_TOOT, winning_OTTO) 
+		self.winner = match (winning_toot, winning_otto) 
 		{
 			(Some(_), Some(_)) => {self.termination = true;None}
 
@@ -159,7 +204,7 @@ impl TootAndOtto {
 
 		self.current_player = self.current_player.switch();
 
-		return true;
+		true
 	}
 
 	fn get_height(&self, col: usize) -> usize 
@@ -171,7 +216,7 @@ impl TootAndOtto {
 			}
 		}
 
-		return 0;
+		0
 	}
 
 	pub fn check_win(&self, player: Player) -> Option<Vec<[usize; 2]>> 
@@ -198,7 +243,7 @@ impl TootAndOtto {
 					}
 				}
 			}
-			return true;
+			true
 		};
 
 		for row_index in 0..NUM_ROWS 
@@ -208,7 +253,7 @@ impl TootAndOtto {
 				let partition = &self.board[row_index][st_index..st_index + 4];
 				if part_checking(partition) 
 				{
-					return Some((0..4).into_iter().map(|i| [row_index, st_index + i]).collect());
+					return Some((0..4).map(|i| [row_index, st_index + i]).collect());
 				}
 			}
 		}
@@ -218,11 +263,11 @@ impl TootAndOtto {
 			for st_index in 0..NUM_ROWS - 3 
 			{
 				let mut partition = vec![];
-				(st_index..st_index + 4).into_iter().for_each(|row_index| partition.push(self.board[row_index][col_index]));
+				(st_index..st_index + 4).for_each(|row_index| partition.push(self.board[row_index][col_index]));
 
 				if part_checking(&partition) 
 				{
-					return Some((0..4).into_iter().map(|i| [st_index + i, col_index]).collect());
+					return Some((0..4).map(|i| [st_index + i, col_index]).collect());
 				}
 			}
 		}
@@ -231,11 +276,11 @@ impl TootAndOtto {
 		{
 			let mut partition = vec![];
 
-			(0..4).into_iter().for_each(|i| partition.push(self.board[NUM_ROWS - 1 - i][col_index + i]));
+			(0..4).for_each(|i| partition.push(self.board[NUM_ROWS - 1 - i][col_index + i]));
 
 			if part_checking(&partition) 
 			{
-				return Some((0..4).into_iter().map(|i| [NUM_ROWS - 1 - i, col_index + i]).collect(),);
+				return Some((0..4).map(|i| [NUM_ROWS - 1 - i, col_index + i]).collect(),);
 			}
 		}
 
@@ -243,18 +288,18 @@ impl TootAndOtto {
 		{
 			let mut partition = vec![];
 
-			(0..4).into_iter().for_each(|i| partition.push(self.board[i][col_index + i]));
+			(0..4).for_each(|i| partition.push(self.board[i][col_index + i]));
 
 			if part_checking(&partition) 
 			{
-				return Some((0..4).into_iter().map(|i| [i, col_index + i]).collect(),);
+				return Some((0..4).map(|i| [i, col_index + i]).collect(),);
 			}
 		}
 
-		return None;
+		None
 	}
 
-	pub fn heuristic_searc_score(&self, player: Player) -> i32 
+	pub fn heuristic_searc_score(&self, player: Player, weights: &EvalWeights) -> i32 
 	{
 	
 		let winning_condition = match player {
@@ -296,30 +341,30 @@ impl TootAndOtto {
 			}
 
 			if mine_chess > 0 && op_chess > 0 {
-				return 0;
+				0
 			}
 			else{
 
 				if mine_chess == 4{
-					return 1000000;
+					weights.win
 				}
 				else if mine_chess == 3{
-					return 50;
+					weights.three
 				}
 				else if mine_chess == 2{
-					return 2;
+					weights.two
 				}
 				else if op_chess == 3 && empty == 1 && mine_chess == 0{
-					return -1000000;
+					weights.opponent_open_three
 				}
 				else if op_chess == 3 && below == 1 && mine_chess == 0{
-					return -100;
+					weights.opponent_three
 				}
 				else if op_chess == 2 && mine_chess == 0{
-					return -10;
+					weights.opponent_two
 				}
 				else{
-					return 0;
+					0
 				}
 			}
 
@@ -330,7 +375,7 @@ impl TootAndOtto {
 			for st_index in 0..NUM_COLS - 3 
 			{
 				let mut partition: Vec<(BoardCell, bool)> = vec![];
-				(st_index..st_index + 4).into_iter().for_each(|col_index| {partition.push((
+				(st_index..st_index + 4).for_each(|col_index| {partition.push((
 					self.board[row_index][col_index],
 					self.current_height[col_index] >= NUM_ROWS - row_index - 1,))
 				});
@@ -344,7 +389,7 @@ impl TootAndOtto {
 			for st_index in 0..NUM_ROWS - 3 
 			{
 				let mut partition: Vec<(BoardCell, bool)> = vec![];
-				(st_index..st_index + 4).into_iter().for_each(|row_index| partition.push((self.board[row_index][col_index], true)));
+				(st_index..st_index + 4).for_each(|row_index| partition.push((self.board[row_index][col_index], true)));
 				score += partition_score(&partition);
 			}
 		}
@@ -353,7 +398,7 @@ impl TootAndOtto {
 		{
 			let mut partition: Vec<(BoardCell, bool)> = vec![];
 
-			(0..4).into_iter().for_each(|i| {partition.push((
+			(0..4).for_each(|i| {partition.push((
 				self.board[NUM_ROWS - 1 - i][col_index + i],
 				self.current_height[col_index + i] >= i,))
 			});
@@ -365,7 +410,7 @@ impl TootAndOtto {
 		{
 			let mut partition: Vec<(BoardCell, bool)> = vec![];
 
-			(0..4).into_iter().for_each(|i| {partition.push((
+			(0..4).for_each(|i| {partition.push((
 				self.board[i][col_index + i],
 				self.current_height[col_index + i] >= NUM_ROWS - i - 1,))
 			});
@@ -374,10 +419,150 @@ impl TootAndOtto {
 		}
 
 
-		return score;
+		score
+	}
+
+	/// Heuristic that only counts lines the owner can still finish with the
+	/// letters left in hand, and weighs open threats by who is to move
+	pub fn inventory_search_score(&self, player: Player, weights: &EvalWeights) -> i32 
+	{
+		let mut score = 0;
+
+		for window in TootAndOtto::windows() 
+		{
+			for owner in [TOOT, OTTO] 
+			{
+				let line_score = self.line_score(&window, owner, weights);
+
+				if owner == player 
+				{
+					score += line_score;
+				}
+				else 
+				{
+					score -= line_score;
+				}
+			}
+		}
+
+		score
+	}
+
+	fn line_score(&self, window: &[[usize; 2]; 4], owner: Player, weights: &EvalWeights) -> i32 
+	{
+		let winning_condition = match owner 
+		{
+			TOOT => [T, O, O, T],
+			OTTO => [O, T, T, O],
+		};
+
+		let mut placed = 0;
+		let mut needed_t = 0;
+		let mut needed_o = 0;
+		let mut open_cell = None;
+
+		for (i, [row, col]) in window.iter().enumerate() 
+		{
+			match self.board[*row][*col] 
+			{
+				Some(letter) => 
+				{
+					if letter != winning_condition[i] 
+					{
+						return 0;
+					}
+					placed += 1;
+				},
+				None => 
+				{
+					match winning_condition[i] 
+					{
+						T => needed_t += 1,
+						O => needed_o += 1,
+					}
+					open_cell = Some((*row, *col, winning_condition[i]));
+				}
+			}
+		}
+
+		// The owner has to spell the word with their own tiles
+		if self.remaining(owner, T) < needed_t || self.remaining(owner, O) < needed_o 
+		{
+			return 0;
+		}
+
+		match placed 
+		{
+			4 => weights.word,
+			3 => 
+			{
+				let (row, col, letter) = open_cell.unwrap();
+
+				if !self.is_playable(row, col) 
+				{
+					return weights.later_threat;
+				}
+
+				if self.current_player == owner 
+				{
+					return weights.winning_threat;
+				}
+
+				// The opponent can only block by dropping the other letter on the gap
+				let block = match letter 
+				{
+					T => O,
+					O => T,
+				};
+
+				if self.remaining(owner.switch(), block) == 0 
+				{
+					return weights.winning_threat;
+				}
+
+				weights.threat
+			},
+			2 => weights.two_letters,
+			1 => weights.one_letter,
+			_ => 0,
+		}
 	}
 
+	/// Whether the next piece dropped in `col` lands on `row`
+	fn is_playable(&self, row: usize, col: usize) -> bool 
+	{
+		self.get_height(col) == NUM_ROWS - 1 - row
+	}
 
+	/// Every line of four cells a word can be spelled on, as [row, col] pairs
+	fn windows() -> Vec<[[usize; 2]; 4]> 
+	{
+		let mut windows = Vec::new();
+
+		for row_index in 0..NUM_ROWS 
+		{
+			for st_index in 0..NUM_COLS - 3 
+			{
+				windows.push([0, 1, 2, 3].map(|i| [row_index, st_index + i]));
+			}
+		}
+
+		for col_index in 0..NUM_COLS 
+		{
+			for st_index in 0..NUM_ROWS - 3 
+			{
+				windows.push([0, 1, 2, 3].map(|i| [st_index + i, col_index]));
+			}
+		}
+
+		for col_index in 0..NUM_COLS - 3 
+		{
+			windows.push([0, 1, 2, 3].map(|i| [NUM_ROWS - 1 - i, col_index + i]));
+			windows.push([0, 1, 2, 3].map(|i| [i, col_index + i]));
+		}
+
+		windows
+	}
 }
 
 
@@ -387,8 +572,8 @@ impl Display for TOenum
 	{
 		match self 
 		{
-			O => write!(f, "{}", "O"),
-			T => write!(f, "{}", "T"),
+			O => write!(f, "O"),
+			T => write!(f, "T"),
 		}
 	}
 }
@@ -0,0 +1,520 @@
+// Exhaustive solver for the 4x6 TOOT and OTTO board.
+//
+// Positions are packed into two 24 bit masks, one nibble per column with the
+// bottom cell in the lowest bit, plus the number of T's TOOT has dropped. That
+// is enough to recover both inventories, so a position fits in a u64 key. The
+// search is a memoised negamax over win/draw/loss, folding mirrored positions
+// onto the same key.
+use super::toot_and_otto::{TootAndOtto, Player::*, TOenum, TOenum::*, NUM_COLS, NUM_ROWS};
+use std::{cmp::Reverse, collections::HashMap};
+
+/// Positions with at most this many pieces on the board are kept in the book
+pub const BOOK_PLY: usize = 4;
+
+const CELLS: usize = NUM_COLS * NUM_ROWS;
+const LETTERS_EACH: u32 = 6;
+const COLUMN_ORDER: [usize; NUM_COLS] = [2, 3, 1, 4, 0, 5];
+
+/// A line of four cells: which cells it covers and where TOOT needs its T's
+#[derive(Copy, Clone)]
+struct Window {
+	cells: u32,
+	toot_tees: u32,
+}
+
+fn cell(row_from_bottom: usize, col: usize) -> u32 {
+	1 << (col * NUM_ROWS + row_from_bottom)
+}
+
+fn windows() -> Vec<Window> {
+	let mut lines: Vec<[(usize, usize); 4]> = Vec::new();
+
+	for row in 0..NUM_ROWS {
+		for col in 0..NUM_COLS - 3 {
+			lines.push([0, 1, 2, 3].map(|i| (row, col + i)));
+		}
+	}
+
+	for col in 0..NUM_COLS {
+		for row in 0..NUM_ROWS - 3 {
+			lines.push([0, 1, 2, 3].map(|i| (row + i, col)));
+		}
+	}
+
+	for col in 0..NUM_COLS - 3 {
+		lines.push([0, 1, 2, 3].map(|i| (i, col + i)));
+		lines.push([0, 1, 2, 3].map(|i| (NUM_ROWS - 1 - i, col + i)));
+	}
+
+	lines
+		.iter()
+		.map(|line| Window {
+			cells: line.iter().fold(0, |mask, &(row, col)| mask | cell(row, col)),
+			toot_tees: cell(line[0].0, line[0].1) | cell(line[3].0, line[3].1),
+		})
+		.collect()
+}
+
+/// Compact copy of a `TootAndOtto` position used inside the search
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Position {
+	filled: u32,
+	tees: u32,
+	toot_tees: u32,
+	ply: u32,
+}
+
+impl Position {
+	pub fn from_board(board: &TootAndOtto) -> Self {
+		let mut position = Position { filled: 0, tees: 0, toot_tees: LETTERS_EACH - board.countings[0][0] as u32, ply: board.next_step as u32 };
+
+		for row in 0..NUM_ROWS {
+			for col in 0..NUM_COLS {
+				if let Some(letter) = board.board[row][col] {
+					let bit = cell(NUM_ROWS - 1 - row, col);
+					position.filled |= bit;
+					if letter == T {
+						position.tees |= bit;
+					}
+				}
+			}
+		}
+
+		position
+	}
+
+	/// Letters left in the hand of the player to move, as (T's, O's)
+	fn hand(&self) -> (u32, u32) {
+		let dropped_tees = self.tees.count_ones();
+		if self.ply.is_multiple_of(2) {
+			let dropped = self.ply.div_ceil(2);
+			(LETTERS_EACH - self.toot_tees, LETTERS_EACH - (dropped - self.toot_tees))
+		} else {
+			let dropped = self.ply / 2;
+			let otto_tees = dropped_tees - self.toot_tees;
+			(LETTERS_EACH - otto_tees, LETTERS_EACH - (dropped - otto_tees))
+		}
+	}
+
+	fn height(&self, col: usize) -> usize {
+		((self.filled >> (col * NUM_ROWS)) & 0xF).count_ones() as usize
+	}
+
+	fn legal_moves(&self) -> impl Iterator<Item = (usize, TOenum)> + '_ {
+		let (tees, os) = self.hand();
+
+		[(T, tees), (O, os)]
+			.into_iter()
+			.filter(|(_, left)| *left > 0)
+			.flat_map(move |(letter, _)| {
+				COLUMN_ORDER
+					.into_iter()
+					.filter(move |col| self.height(*col) < NUM_ROWS)
+					.map(move |col| (col, letter))
+			})
+	}
+
+	fn play(&self, col: usize, letter: TOenum) -> (Position, u32) {
+		let bit = cell(self.height(col), col);
+		let mut next = *self;
+
+		next.filled |= bit;
+		if letter == T {
+			next.tees |= bit;
+			if self.ply.is_multiple_of(2) {
+				next.toot_tees += 1;
+			}
+		}
+		next.ply += 1;
+
+		(next, bit)
+	}
+
+	fn key(&self) -> u64 {
+		((self.filled as u64) << 27) | ((self.tees as u64) << 3) | self.toot_tees as u64
+	}
+
+	/// Key shared by a position and its mirror image
+	fn canonical_key(&self) -> u64 {
+		let mirrored = Position { filled: mirror(self.filled), tees: mirror(self.tees), ..*self };
+		self.key().min(mirrored.key())
+	}
+}
+
+fn mirror(mask: u32) -> u32 {
+	(0..NUM_COLS).fold(0, |mirrored, col| {
+		mirrored | (((mask >> (col * NUM_ROWS)) & 0xF) << ((NUM_COLS - 1 - col) * NUM_ROWS))
+	})
+}
+
+/// Win/draw/loss table for the opening positions, loaded by `toot_ai`
+pub struct Book {
+	values: HashMap<u64, i8>,
+}
+
+impl Book {
+	/// Reads the `key: u64, value: i8` little-endian records written by `Book::to_bytes`
+	pub fn from_bytes(bytes: &[u8]) -> Self {
+		let values = bytes
+			.chunks_exact(9)
+			.map(|record| {
+				let mut key = [0; 8];
+				key.copy_from_slice(&record[..8]);
+				(u64::from_le_bytes(key), record[8] as i8)
+			})
+			.collect();
+
+		Book { values }
+	}
+
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut records: Vec<(&u64, &i8)> = self.values.iter().collect();
+		records.sort();
+
+		let mut bytes = Vec::with_capacity(records.len() * 9);
+		for (key, value) in records {
+			bytes.extend_from_slice(&key.to_le_bytes());
+			bytes.push(*value as u8);
+		}
+		bytes
+	}
+
+	pub fn len(&self) -> usize {
+		self.values.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.values.is_empty()
+	}
+
+	fn get(&self, position: &Position) -> Option<i32> {
+		self.values.get(&position.canonical_key()).map(|value| *value as i32)
+	}
+}
+
+/// Memoised negamax over win (1), draw (0) and loss (-1) for the player to move
+pub struct Solver {
+	windows: Vec<Vec<Window>>,
+	table: Vec<u64>,
+	book: Option<Book>,
+	pub nodes: u64,
+}
+
+impl Solver {
+	/// Creates a solver with a transposition table of 2^`table_bits` entries
+	pub fn new(table_bits: usize, book: Option<Book>) -> Self {
+		let lines = windows();
+		let windows = (0..CELLS)
+			.map(|index| lines.iter().filter(|window| window.cells & 1 << index != 0).copied().collect())
+			.collect();
+
+		Solver { windows, table: vec![0; 1 << table_bits], book, nodes: 0 }
+	}
+
+	/// Value of `board` for the player to move
+	pub fn solve(&mut self, board: &TootAndOtto) -> i32 {
+		if board.termination {
+			return match board.winner {
+				None => 0,
+				Some(player) if player == board.current_player => 1,
+				Some(_) => -1,
+			};
+		}
+
+		self.negamax(&Position::from_board(board), -1, 1)
+	}
+
+	/// Every legal move of `board` with its value for the player making it
+	pub fn move_values(&mut self, board: &TootAndOtto) -> Vec<(usize, TOenum, i32)> {
+		let position = Position::from_board(board);
+
+		position
+			.legal_moves()
+			.map(|(col, letter)| (col, letter, self.value_after(&position, col, letter)))
+			.collect()
+	}
+
+	/// Builds the book of every reachable position with up to `BOOK_PLY` pieces.
+	/// The deepest positions are solved first so every shallower one is answered
+	/// from the entries already in the book.
+	pub fn build_book(&mut self) -> Book {
+		let mut levels = vec![vec![Position::from_board(&TootAndOtto::new())]];
+
+		while levels.len() <= BOOK_PLY {
+			let mut next_level = HashMap::new();
+
+			for position in levels.last().unwrap() {
+				for (col, letter) in position.legal_moves() {
+					let (next, bit) = position.play(col, letter);
+					if self.outcome(&next, bit).is_none() {
+						next_level.entry(next.canonical_key()).or_insert(next);
+					}
+				}
+			}
+
+			levels.push(next_level.into_values().collect());
+		}
+
+		self.book = Some(Book { values: HashMap::new() });
+
+		for level in levels.iter().rev() {
+			for position in level {
+				let value = self.negamax(position, -1, 1) as i8;
+				self.book.as_mut().unwrap().values.insert(position.canonical_key(), value);
+			}
+		}
+
+		self.book.take().unwrap()
+	}
+
+	/// Value of dropping `letter` in `col` for the player making the move
+	pub fn move_value(&mut self, board: &TootAndOtto, col: usize, letter: TOenum) -> i32 {
+		self.value_after(&Position::from_board(board), col, letter)
+	}
+
+	fn value_after(&mut self, position: &Position, col: usize, letter: TOenum) -> i32 {
+		let (next, bit) = position.play(col, letter);
+		match self.outcome(&next, bit) {
+			Some(value) => value,
+			None => -self.negamax(&next, -1, 1),
+		}
+	}
+
+	/// Result for the player who just dropped on `bit`, if the game is over
+	fn outcome(&self, position: &Position, bit: u32) -> Option<i32> {
+		let mut toot = false;
+		let mut otto = false;
+
+		for window in &self.windows[bit.trailing_zeros() as usize] {
+			if position.filled & window.cells != window.cells {
+				continue;
+			}
+			let tees = position.tees & window.cells;
+			toot |= tees == window.toot_tees;
+			otto |= tees == window.cells ^ window.toot_tees;
+		}
+
+		let mover = if position.ply % 2 == 1 { TOOT } else { OTTO };
+		match (toot, otto) {
+			(true, true) => Some(0),
+			(true, false) => Some(if mover == TOOT { 1 } else { -1 }),
+			(false, true) => Some(if mover == OTTO { 1 } else { -1 }),
+			(false, false) if position.ply as usize == CELLS => Some(0),
+			(false, false) => None,
+		}
+	}
+
+	fn negamax(&mut self, position: &Position, mut alpha: i32, mut beta: i32) -> i32 {
+		self.nodes += 1;
+
+		if let Some(book) = &self.book {
+			if let Some(value) = book.get(position) {
+				return value;
+			}
+		}
+
+		let key = position.canonical_key();
+		let slot = (key.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32) as usize % self.table.len();
+		// Moves are stored for the canonical side of the mirror
+		let mirrored = position.key() != key;
+		let (mut lower, mut upper) = (-1, 1);
+		let mut hash_move = None;
+
+		let entry = self.table[slot];
+		if entry >> 10 == key | 1 << 51 {
+			lower = ((entry >> 3) & 0x7) as i32 - 1;
+			upper = (entry & 0x7) as i32 - 1;
+			if lower == upper || lower >= beta {
+				return lower;
+			}
+			if upper <= alpha {
+				return upper;
+			}
+			alpha = alpha.max(lower);
+			beta = beta.min(upper);
+			hash_move = Some(((entry >> 6) & 0xF) as usize);
+		}
+
+		let (alpha_start, beta_start) = (alpha, beta);
+		let mut best = -2;
+		let mut best_move = 0;
+		let mut children = [(*position, 0, 0); 2 * NUM_COLS];
+		let mut count = 0;
+
+		for (col, letter) in position.legal_moves() {
+			let (next, bit) = position.play(col, letter);
+			let stored_col = if mirrored { NUM_COLS - 1 - col } else { col };
+			let move_index = stored_col * 2 + if letter == T { 0 } else { 1 };
+
+			match self.outcome(&next, bit) {
+				Some(1) => {
+					self.store(slot, key, move_index, 1, 1);
+					return 1;
+				},
+				Some(value) => {
+					if value > best {
+						best = value;
+						best_move = move_index;
+					}
+				},
+				// Handing the opponent a finished word next turn loses on the spot
+				None if self.wins_now(&next) => {
+					if -1 > best {
+						best = -1;
+						best_move = move_index;
+					}
+				},
+				None => {
+					// Try the remembered best move first, then moves that leave threats behind
+					let priority = if hash_move == Some(move_index) { 100 } else { self.winning_drops(&next, position.ply.is_multiple_of(2)) };
+					children[count] = (next, move_index, priority);
+					count += 1;
+				},
+			}
+		}
+		alpha = alpha.max(best);
+		children[..count].sort_by_key(|child| Reverse(child.2));
+
+		for (next, move_index, _) in &children[..count] {
+			if alpha >= beta {
+				break;
+			}
+
+			let value = -self.negamax(next, -beta, -alpha);
+			if value > best {
+				best = value;
+				best_move = *move_index;
+			}
+			alpha = alpha.max(best);
+		}
+
+		if best <= alpha_start {
+			upper = best;
+		} else if best >= beta_start {
+			lower = best;
+		} else {
+			lower = best;
+			upper = best;
+		}
+
+		self.store(slot, key, best_move, lower, upper);
+		best
+	}
+
+	/// Whether the player to move can finish their word with a single drop
+	fn wins_now(&self, position: &Position) -> bool {
+		self.winning_drops(position, position.ply.is_multiple_of(2)) > 0
+	}
+
+	/// Number of playable cells where one drop would finish `toot`'s word (or
+	/// OTTO's) without also finishing the other one. Inventories are ignored
+	/// for the player who is not to move.
+	fn winning_drops(&self, position: &Position, toot: bool) -> u32 {
+		let (tees, os) = if toot == position.ply.is_multiple_of(2) { position.hand() } else { (1, 1) };
+		let mut drops = 0;
+
+		for col in 0..NUM_COLS {
+			let height = position.height(col);
+			if height == NUM_ROWS {
+				continue;
+			}
+
+			let bit = cell(height, col);
+			// Which words a T (index 0) or an O (index 1) on this cell would finish
+			let mut toot_words = [false; 2];
+			let mut otto_words = [false; 2];
+
+			for window in &self.windows[bit.trailing_zeros() as usize] {
+				let others = window.cells ^ bit;
+				if position.filled & others != others {
+					continue;
+				}
+
+				let tees_placed = position.tees & others;
+				let needs_t = window.toot_tees & bit != 0;
+				if tees_placed == window.toot_tees & others {
+					toot_words[if needs_t { 0 } else { 1 }] = true;
+				} else if tees_placed == (window.cells ^ window.toot_tees) & others {
+					otto_words[if needs_t { 1 } else { 0 }] = true;
+				}
+			}
+
+			let (mine, theirs) = if toot { (toot_words, otto_words) } else { (otto_words, toot_words) };
+			if (tees > 0 && mine[0] && !theirs[0]) || (os > 0 && mine[1] && !theirs[1]) {
+				drops += 1;
+			}
+		}
+
+		drops
+	}
+
+	fn store(&mut self, slot: usize, key: u64, best_move: usize, lower: i32, upper: i32) {
+		self.table[slot] = ((key | 1 << 51) << 10) | ((best_move as u64) << 6) | (((lower + 1) as u64) << 3) | (upper + 1) as u64;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::toot_and_otto::toot_ai;
+
+	/// TOOT to move with T O O _ on the bottom row, one T from spelling the word
+	fn toot_threat() -> TootAndOtto {
+		let mut board = TootAndOtto::new();
+		for (letter, col) in [(T, 0), (O, 1), (O, 2), (T, 5)] {
+			assert!(board.drop(letter, col));
+		}
+		board
+	}
+
+	#[test]
+	fn finds_the_winning_drop() {
+		let board = toot_threat();
+		let mut solver = Solver::new(16, None);
+
+		assert_eq!(solver.solve(&board), 1);
+		assert_eq!(solver.move_value(&board, 3, T), 1);
+		assert_eq!(toot_ai::perfect_move(board), (3, T));
+	}
+
+	#[test]
+	fn scores_finished_games_for_the_player_to_move() {
+		let mut board = toot_threat();
+		board.drop(T, 3);
+
+		assert!(board.termination);
+		assert_eq!(Solver::new(16, None).solve(&board), -1);
+	}
+
+	#[test]
+	fn mirrored_positions_share_a_key() {
+		let mut left = TootAndOtto::new();
+		left.drop(T, 0);
+		let mut right = TootAndOtto::new();
+		right.drop(T, NUM_COLS - 1);
+
+		assert_eq!(Position::from_board(&left).canonical_key(), Position::from_board(&right).canonical_key());
+		assert_ne!(Position::from_board(&left).key(), Position::from_board(&right).key());
+	}
+
+	#[test]
+	fn book_survives_its_byte_format() {
+		let book = Book::from_bytes(toot_ai::BOOK);
+		assert_eq!(book.len() * 9, toot_ai::BOOK.len());
+		assert_eq!(Book::from_bytes(&book.to_bytes()).values, book.values);
+	}
+
+	#[test]
+	fn book_agrees_with_the_search() {
+		let book = Book::from_bytes(toot_ai::BOOK);
+		let mut board = TootAndOtto::new();
+		board.drop(T, 2);
+		board.drop(O, 3);
+		board.drop(O, 2);
+		board.drop(T, 3);
+
+		let position = Position::from_board(&board);
+		let stored = book.get(&position).expect("four-piece positions are in the book");
+		assert_eq!(Solver::new(22, None).negamax(&position, -1, 1), stored);
+	}
+}
@@ -0,0 +1,134 @@
+// Shared types for the `analyze` functions of both AIs
+
+/// Scores further than this from zero are forced wins or losses
+pub const WIN_THRESHOLD: i32 = i32::MAX - 100;
+
+/// How long an analysis may search
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Limit {
+	/// Search exactly this many plies
+	Depth(usize),
+	/// Deepen one ply at a time until this many milliseconds have passed,
+	/// keeping the deepest search that finished
+	Millis(u64),
+}
+
+/// What the search thinks of one legal move
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveEval<M> {
+	pub mv: M,
+
+	/// Minimax value for the player to move, on the same scale as `score_moves`
+	pub score: i32,
+
+	/// Expected continuation, starting with `mv`
+	pub pv: Vec<M>,
+
+	/// Plies until the game is decided, positive when the player to move
+	/// wins and negative when they lose, `None` when the search saw no forced result
+	pub mate: Option<i32>,
+}
+
+impl<M> MoveEval<M> {
+	pub fn new(mv: M, score: i32, pv: Vec<M>) -> Self {
+		let mate = if score > WIN_THRESHOLD {
+			Some(pv.len() as i32)
+		} else if score < -WIN_THRESHOLD {
+			Some(-(pv.len() as i32))
+		} else {
+			None
+		};
+
+		MoveEval { mv, score, pv, mate }
+	}
+}
+
+/// Every legal move of a position, best first
+#[derive(Clone, Debug, PartialEq)]
+pub struct Analysis<M> {
+	/// Plies searched from the position
+	pub depth: usize,
+	pub moves: Vec<MoveEval<M>>,
+}
+
+/// Share of an evaluation bar, from 0 to 1, that belongs to the player to
+/// move; a score of `scale` fills about 73% of it
+pub fn advantage<M>(eval: &MoveEval<M>, scale: f64) -> f64 {
+	match eval.mate {
+		Some(plies) if plies > 0 => 1.0,
+		Some(_) => 0.0,
+		None => 1.0 / (1.0 + (-(eval.score as f64) / scale).exp()),
+	}
+}
+
+/// Runs `search` (which returns every move's evaluation at a depth, or `None`
+/// when it ran out of time) as `limit` allows; `max_depth` is the number of
+/// plies left in the game
+pub fn deepen<M, F>(limit: Limit, max_depth: usize, mut search: F) -> Analysis<M>
+where
+	F: FnMut(usize, &Deadline) -> Option<Vec<MoveEval<M>>>,
+{
+	let mut analysis = Analysis { depth: 0, moves: Vec::new() };
+	if max_depth == 0 {
+		return analysis;
+	}
+
+	let deadline = Deadline::new(limit);
+	let (first, last) = match limit {
+		Limit::Depth(depth) => (depth.clamp(1, max_depth), depth.clamp(1, max_depth)),
+		Limit::Millis(_) => (1, max_depth),
+	};
+
+	for depth in first..=last {
+		// The first search always finishes, so a timed analysis has something to show
+		let moves = if depth == first { search(depth, &Deadline(None)) } else { search(depth, &deadline) };
+
+		match moves {
+			Some(mut moves) => {
+				moves.sort_by_key(|eval| std::cmp::Reverse(eval.score));
+				analysis = Analysis { depth, moves };
+			}
+			None => break,
+		}
+
+		// Searching deeper cannot change moves that are already decided
+		if analysis.moves.iter().all(|eval| eval.mate.is_some()) || deadline.passed() {
+			break;
+		}
+	}
+
+	analysis
+}
+
+/// Point in time after which a timed search gives up
+pub struct Deadline(Option<f64>);
+
+impl Deadline {
+	pub fn new(limit: Limit) -> Self {
+		match limit {
+			Limit::Depth(_) => Deadline(None),
+			Limit::Millis(millis) => Deadline(Some(now_millis() + millis as f64)),
+		}
+	}
+
+	pub fn passed(&self) -> bool {
+		match self.0 {
+			None => false,
+			Some(end) => now_millis() >= end,
+		}
+	}
+}
+
+/// `std::time::Instant` panics in the browser, so the page reads the clock from JavaScript
+#[cfg(all(feature = "wasm", not(feature = "native")))]
+fn now_millis() -> f64 {
+	js_sys::Date::now()
+}
+
+#[cfg(feature = "native")]
+fn now_millis() -> f64 {
+	std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map(|elapsed| elapsed.as_secs_f64() * 1000.0)
+		.unwrap_or(0.0)
+}
@@ -0,0 +1,67 @@
+// Weights of the window-counting heuristics (`heuristic_searc_score`) of both games
+// and of the inventory heuristic of TOOT and OTTO (`inventory_search_score`)
+use serde::{Deserialize, Serialize};
+
+/// What each kind of four-cell window is worth to the side being scored
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvalWeights {
+	/// A finished line of our own
+	pub win: i32,
+	/// Three of ours and an empty cell
+	pub three: i32,
+	/// Two of ours and two empty cells
+	pub two: i32,
+	/// Three of theirs and an empty cell that can be played right now
+	pub opponent_open_three: i32,
+	/// Three of theirs and an empty cell that still needs a piece under it
+	pub opponent_three: i32,
+	/// Two of theirs and two empty cells
+	pub opponent_two: i32,
+	/// Each of our pieces in the centre column, Connect 4 only
+	pub center: i32,
+	/// The rest are line scores of the TOOT and OTTO inventory heuristic only:
+	/// a spelled word
+	pub word: i32,
+	/// Three letters of a word whose gap the owner fills next, or the opponent cannot block
+	pub winning_threat: i32,
+	/// Three letters of a word whose gap is playable but can be blocked
+	pub threat: i32,
+	/// Three letters of a word whose gap is not playable yet
+	pub later_threat: i32,
+	/// Two letters of a word, then one
+	pub two_letters: i32,
+	pub one_letter: i32,
+}
+
+impl Default for EvalWeights {
+	/// The weights the heuristics were written with
+	fn default() -> Self {
+		EvalWeights {
+			win: 1000000,
+			three: 50,
+			two: 2,
+			opponent_open_three: -1000000,
+			opponent_three: -100,
+			opponent_two: -10,
+			center: 10,
+			word: 1000000,
+			winning_threat: 100000,
+			threat: 200,
+			later_threat: 50,
+			two_letters: 5,
+			one_letter: 1,
+		}
+	}
+}
+
+impl EvalWeights {
+	/// Reads weights from JSON; missing fields keep their default value
+	pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+		serde_json::from_str(json)
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).expect("Error serializing weights")
+	}
+}
@@ -0,0 +1,134 @@
+// Post-game review shared by both games: the AIs replay the moves and
+// `judge` compares each one with the search's best move
+use crate::types::analysis::{advantage, Analysis};
+use std::fmt::{Display, Formatter, Result};
+
+/// Share of the evaluation bar a move may give away and still count as good
+const GOOD_LOSS: f64 = 0.1;
+
+/// Share of the evaluation bar a move may give away and still only be a mistake
+const MISTAKE_LOSS: f64 = 0.25;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Verdict {
+	Best,
+	Good,
+	Mistake,
+	Blunder,
+	/// The analysis did not reach the move, so it is not rated
+	Unanalysed,
+}
+
+impl Display for Verdict {
+	fn fmt(&self, f: &mut Formatter) -> Result {
+		match self {
+			Verdict::Best => write!(f, "Best"),
+			Verdict::Good => write!(f, "Good"),
+			Verdict::Mistake => write!(f, "Mistake"),
+			Verdict::Blunder => write!(f, "Blunder"),
+			Verdict::Unanalysed => write!(f, "Unanalysed"),
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveReview<M> {
+	pub mv: M,
+
+	/// What the search would have played instead
+	pub best: M,
+
+	/// Share of the evaluation bar the mover gave away compared with `best`
+	pub loss: f64,
+
+	pub verdict: Verdict,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Review<M> {
+	pub moves: Vec<MoveReview<M>>,
+
+	/// Index of the move that gave away the most, if any move was worse than good
+	pub turning_point: Option<usize>,
+}
+
+/// Rates `mv` against the analysis of the position it was played in. A move the
+/// analysis left out, as a cut short search or an illegal move can, is `Unanalysed`
+pub fn judge<M: Copy + PartialEq>(mv: M, analysis: &Analysis<M>, scale: f64) -> MoveReview<M> {
+	let best = analysis.moves.first();
+	let (best, played) = match (best, analysis.moves.iter().find(|eval| eval.mv == mv)) {
+		(Some(best), Some(played)) => (best, played),
+		_ => return MoveReview { mv, best: best.map_or(mv, |best| best.mv), loss: 0.0, verdict: Verdict::Unanalysed },
+	};
+	let loss = advantage(best, scale) - advantage(played, scale);
+
+	let verdict = if played.score == best.score {
+		Verdict::Best
+	} else if loss < GOOD_LOSS {
+		Verdict::Good
+	} else if loss < MISTAKE_LOSS {
+		Verdict::Mistake
+	} else {
+		Verdict::Blunder
+	};
+
+	MoveReview { mv, best: best.mv, loss, verdict }
+}
+
+impl<M> Review<M> {
+	pub fn new(moves: Vec<MoveReview<M>>) -> Self {
+		let turning_point = moves
+			.iter()
+			.enumerate()
+			.filter(|(_, review)| review.verdict == Verdict::Mistake || review.verdict == Verdict::Blunder)
+			.max_by(|a, b| a.1.loss.partial_cmp(&b.1.loss).unwrap())
+			.map(|(index, _)| index);
+
+		Review { moves, turning_point }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::types::analysis::MoveEval;
+
+	fn analysis(scores: &[(usize, i32)]) -> Analysis<usize> {
+		Analysis { depth: 1, moves: scores.iter().map(|(mv, score)| MoveEval::new(*mv, *score, vec![*mv])).collect() }
+	}
+
+	#[test]
+	fn leaves_a_move_with_nothing_to_compare_unanalysed() {
+		let review = judge(3, &analysis(&[]), 100.0);
+
+		assert_eq!(review, MoveReview { mv: 3, best: 3, loss: 0.0, verdict: Verdict::Unanalysed });
+	}
+
+	#[test]
+	fn leaves_a_move_missing_from_the_analysis_unanalysed() {
+		let review = judge(6, &analysis(&[(3, 50), (2, 40)]), 100.0);
+
+		assert_eq!(review, MoveReview { mv: 6, best: 3, loss: 0.0, verdict: Verdict::Unanalysed });
+		assert_eq!(Review::new(vec![review]).turning_point, None);
+	}
+
+	#[test]
+	fn grades_moves_by_what_they_give_away() {
+		let position = analysis(&[(3, 50), (2, 40), (4, 0), (0, -300)]);
+
+		assert_eq!(judge(3, &position, 100.0).verdict, Verdict::Best);
+		assert_eq!(judge(2, &position, 100.0).verdict, Verdict::Good);
+		assert_eq!(judge(4, &position, 100.0).verdict, Verdict::Mistake);
+		assert_eq!(judge(0, &position, 100.0).verdict, Verdict::Blunder);
+		assert_eq!(judge(0, &position, 100.0).best, 3);
+	}
+
+	#[test]
+	fn turns_on_the_worst_mistake() {
+		let position = analysis(&[(3, 50), (2, -60), (0, -300)]);
+		let review = Review::new(vec![judge(3, &position, 100.0), judge(0, &position, 100.0), judge(2, &position, 100.0)]);
+
+		assert_eq!(review.turning_point, Some(1));
+		assert_eq!(Review::new(vec![judge(3, &position, 100.0)]).turning_point, None);
+	}
+}
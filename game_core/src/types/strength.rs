@@ -0,0 +1,169 @@
+use rand::Rng;
+
+/// Highest selectable AI level; level 1 is the weakest
+pub const MAX_LEVEL: u8 = 10;
+
+/// Scores beyond this are forced wins or losses and are clamped before the softmax
+const SCORE_CAP: f64 = 1_000_000.0;
+
+/// How many temperatures below the best score a move may be and still be one
+/// `choose` might pick: past this its softmax weight is under a millionth of the best's
+const BAND: f64 = 14.0;
+
+/// Reads a fractional level off a per-level Elo table, interpolating linearly
+pub fn level_elo(table: &[i32], level: f64) -> i32 {
+	let level = level.clamp(1.0, table.len() as f64);
+	let below = level.floor() as usize;
+	let above = (below + 1).min(table.len());
+	let fraction = level - below as f64;
+
+	(table[below - 1] as f64 * (1.0 - fraction) + table[above - 1] as f64 * fraction).round() as i32
+}
+
+/// The knobs that decide how well an AI plays
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Strength {
+	/// How many plies the minimax looks ahead
+	pub depth: usize,
+
+	/// Softmax temperature over the root move scores, 0 always plays a best move
+	pub temperature: f64,
+
+	/// Chance of dropping a random legal piece instead of searching at all
+	pub blunder: f64,
+}
+
+impl Strength {
+	/// Interpolates the knobs for any level between 1 and `MAX_LEVEL`,
+	/// fractional levels included
+	pub fn from_level(level: f64) -> Self {
+		let t = ((level - 1.0) / (MAX_LEVEL as f64 - 1.0)).clamp(0.0, 1.0);
+		let weakness = (1.0 - t) * (1.0 - t);
+
+		Strength {
+			depth: 1 + (t * 4.0).round() as usize,
+			temperature: 40.0 * weakness,
+			blunder: 0.6 * weakness,
+		}
+	}
+
+	/// Whether this move should be a random blunder
+	pub fn blunders<R: Rng>(&self, rng: &mut R) -> bool {
+		rng.gen_range(0.0, 1.0) < self.blunder
+	}
+
+	/// Samples an index into `scores` with a softmax at this strength's temperature,
+	/// `None` when there are no scores to choose from
+	pub fn choose<R: Rng>(&self, scores: &[i32], rng: &mut R) -> Option<usize> {
+		if scores.is_empty() {
+			return None;
+		}
+
+		let clamped: Vec<f64> = scores.iter().map(|score| (*score as f64).clamp(-SCORE_CAP, SCORE_CAP)).collect();
+		let best = clamped.iter().cloned().fold(f64::MIN, f64::max);
+
+		let weights: Vec<f64> = clamped
+			.iter()
+			.map(|score| {
+				if self.temperature <= 0.0 {
+					if *score == best { 1.0 } else { 0.0 }
+				} else {
+					((score - best) / self.temperature).exp()
+				}
+			})
+			.collect();
+
+		let mut pick = rng.gen_range(0.0, weights.iter().sum::<f64>());
+		for (index, weight) in weights.iter().enumerate() {
+			if pick < *weight {
+				return Some(index);
+			}
+			pick -= weight;
+		}
+
+		// Rounding left the pick past the last weight: the best move has weight 1, so take it
+		clamped.iter().position(|score| *score == best)
+	}
+
+	/// Whether `choose` could pick `scores[index]`. Unlike the pick itself this needs
+	/// no `exp`, whose last bits differ between platforms, so a server can check a move
+	/// the browser chose
+	pub fn within_band(&self, scores: &[i32], index: usize) -> bool {
+		let clamp = |score: i32| (score as f64).clamp(-SCORE_CAP, SCORE_CAP);
+		let best = match scores.iter().map(|score| clamp(*score)).reduce(f64::max) {
+			Some(best) => best,
+			None => return false,
+		};
+
+		match scores.get(index) {
+			Some(score) => best - clamp(*score) <= BAND * self.temperature.max(0.0),
+			None => false,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::{rngs::StdRng, SeedableRng};
+
+	#[test]
+	fn has_nothing_to_choose_from_no_scores() {
+		let mut rng = StdRng::seed_from_u64(1);
+
+		assert_eq!(Strength::from_level(1.0).choose(&[], &mut rng), None);
+		assert_eq!(Strength::from_level(MAX_LEVEL as f64).choose(&[], &mut rng), None);
+	}
+
+	#[test]
+	fn zero_temperature_plays_the_best_move() {
+		let strength = Strength { depth: 1, temperature: 0.0, blunder: 0.0 };
+		let mut rng = StdRng::seed_from_u64(2);
+
+		for _ in 0..100 {
+			assert_eq!(strength.choose(&[3, -2, 7, 7, 1], &mut rng).map(|index| index == 2 || index == 3), Some(true));
+		}
+	}
+
+	#[test]
+	fn picks_stay_in_range_at_any_temperature() {
+		let scores = [i32::MIN, -1_000_000_000, 0, 5, i32::MAX];
+		let mut rng = StdRng::seed_from_u64(3);
+
+		for level in 1..=MAX_LEVEL {
+			for _ in 0..100 {
+				let index = Strength::from_level(level as f64).choose(&scores, &mut rng);
+				assert!(matches!(index, Some(index) if index < scores.len()));
+			}
+		}
+	}
+
+	#[test]
+	fn the_band_holds_every_move_choose_picks() {
+		let scores = [40, 10, 39, -1_000_000_000];
+		let exact = Strength { depth: 1, temperature: 0.0, blunder: 0.0 };
+		let warm = Strength { depth: 1, temperature: 1.0, blunder: 0.0 };
+		let mut rng = StdRng::seed_from_u64(4);
+
+		assert!(exact.within_band(&scores, 0));
+		assert!(!exact.within_band(&scores, 2));
+		assert!(warm.within_band(&scores, 2));
+		assert!(!warm.within_band(&scores, 1));
+		assert!(!warm.within_band(&scores, 4));
+		assert!(!warm.within_band(&[], 0));
+		for _ in 0..1000 {
+			assert!(warm.within_band(&scores, warm.choose(&scores, &mut rng).unwrap()));
+		}
+	}
+
+	#[test]
+	fn levels_span_the_table() {
+		let table = [800, 1000, 1400];
+
+		assert_eq!(level_elo(&table, 0.0), 800);
+		assert_eq!(level_elo(&table, 1.5), 900);
+		assert_eq!(level_elo(&table, 3.0), 1400);
+		assert_eq!(level_elo(&table, 9.0), 1400);
+		assert_eq!(Strength::from_level(MAX_LEVEL as f64), Strength { depth: 5, temperature: 0.0, blunder: 0.0 });
+	}
+}
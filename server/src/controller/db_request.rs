@@ -1,34 +1,75 @@
 use mongodb::error::Error;
 use crate::MongoDB;
 use rocket::http::RawStr;
+use rocket::request::LenientForm;
+use rocket::State;
 use rocket_contrib::json::Json;
 use crate::document_update::{ChampStats, GameStats, ScoreUpdate, User};
+use crate::games::{GameHistory, GameStart, HistoryQuery, StoredGame};
+use crate::session::{AuthUser, SessionKey, SessionResponse};
+use game_core::record::{Ai, StartedGame};
 
-// Post request to update game scores
+// Lowest computer level a win scores against. Below it the AI blunders so often, and picks
+// from so wide a band of moves, that almost any move replays as one it could have played
+const SCORED_LEVEL: f64 = 4.0;
+
+// Post request to start a game against the computer, with the seed its AI is to draw from;
+// null when the game could not be started, and then it cannot score
+#[post("/start_game", format = "application/json", data = "<game>")]
+pub fn start_game(user: AuthUser, db: State<MongoDB>, game: Json<GameStart>) -> Json<Option<StartedGame>> {
+    Json(db.inner().clone().start_game(&user.username, &user.session_id, &game).ok())
+}
+
+// Post request to update the logged in user's game scores
 #[post("/update_score", format = "application/json", data = "<score>")]
-pub fn update_score(score: Json<ScoreUpdate>) -> Json<String> {
-    return match MongoDB::new() {
-        // Establish connection
-        Ok(mut db) => {
-            match db.update_score(&score.username, score.mode, score.result, score.human_flag) {
-                // Update score
-                Ok(res) => {
-                    if res {
-                        Json(String::from("Update success"))
-                    } else {
-                        Json(String::from("Update failed"))
-                    }
-                }
-                Err(_) => Json(String::from("Failure:")),
+pub fn update_score(user: AuthUser, db: State<MongoDB>, score: Json<ScoreUpdate>) -> Json<String> {
+    // Replay the game through the engines instead of taking the claimed result on trust
+    match score.game.verify() {
+        Ok(result) if result == score.result => {}
+        Ok(_) => return Json(String::from("Update rejected: the result does not match the moves")),
+        Err(reason) => return Json(format!("Update rejected: {}", reason)),
+    }
+    let mut db = db.inner().clone();
+    // A game against the computer only counts if the server started it for this session,
+    // so the AI drew from the server's seed at the level it was told of
+    if score.game.ai.is_some() {
+        match db.take_started_game(&user.username, &user.session_id, &score) {
+            Ok(true) => {}
+            Ok(false) => return Json(String::from("Update rejected: the server did not start this game")),
+            Err(_) => return Json(String::from("Failure:")),
+        }
+    }
+    // Every finished game is kept once, but games between two humans do not score.
+    // Keeping it first means a game sent again is turned away before it scores twice
+    match db.add_game(&user.username, &score) {
+        Ok(true) => {}
+        Ok(false) => return Json(String::from("Update rejected: this game was already recorded")),
+        Err(_) => return Json(String::from("Failure:")),
+    }
+    if score.game.ai.is_none() {
+        return Json(String::from("Update success"));
+    }
+    // A win over the weakest levels proves too little to score, like a win with hints
+    let hinted = match score.game.ai {
+        Some(Ai::Level(level)) if level < SCORED_LEVEL => 1,
+        _ => score.hinted,
+    };
+    match db.update_score(&user.username, score.game.mode, score.result, hinted) {
+        // Update score
+        Ok(res) => {
+            if res {
+                Json(String::from("Update success"))
+            } else {
+                Json(String::from("Update failed"))
             }
-        },
-        Err(_) => Json(String::from("Failure: Bad Connection")),
+        }
+        Err(_) => Json(String::from("Failure:")),
     }
 }
 
-// Get request to obtain user stats
-#[get("/scores/<username>")]
-pub fn get_scores(username: &RawStr) -> Json<(GameStats, ChampStats, ChampStats)> {
+// Get request to obtain the logged in user's stats
+#[get("/scores")]
+pub fn get_scores(user: AuthUser, db: State<MongoDB>) -> Json<(GameStats, ChampStats, ChampStats)> {
     let err = GameStats {
         // error struct. Sends this if no user found
         username: "".to_string(),
@@ -57,56 +98,69 @@ pub fn get_scores(username: &RawStr) -> Json<(GameStats, ChampStats, ChampStats)
         lose_count: 0,
         tie_count: 0
     };
-    return match MongoDB::new() {
-        // Mongodb Connection
-        Ok(mut db) => {
-            match db.get_game_score(username.to_string()) {
-                // Gets the user's game statistics
-                Ok(stats) => {
-                    if stats.is_none() {
-                        Json((err, err_champ_c4, err_champ_ot))
-                    } else {
-                        Json(stats.unwrap())
-                    }
-                }
-                Err(_) => Json((err, err_champ_c4, err_champ_ot)),
+    match db.inner().clone().get_game_score(user.username) {
+        // Gets the user's game statistics
+        Ok(stats) => {
+            if stats.is_none() {
+                Json((err, err_champ_c4, err_champ_ot))
+            } else {
+                Json(stats.unwrap())
             }
         }
         Err(_) => Json((err, err_champ_c4, err_champ_ot)),
     }
 }
 
-// Post request to create a new user
+// Get request for a page of a player's finished games, for any logged in user
+#[get("/games/<username>?<query..>")]
+pub fn game_history(_user: AuthUser, db: State<MongoDB>, username: &RawStr, query: LenientForm<HistoryQuery>) -> Json<GameHistory> {
+    let empty = GameHistory { games: Vec::new(), page: 1, per_page: 0, total: 0 };
+    match db.inner().clone().game_history(&username.url_decode_lossy(), &query) {
+        Ok(history) => Json(history),
+        Err(_) => Json(empty),
+    }
+}
+
+// Get request for one finished game with its moves, for any logged in user; null when there is no such game
+#[get("/game/<id>")]
+pub fn game_record(_user: AuthUser, db: State<MongoDB>, id: &RawStr) -> Json<Option<StoredGame>> {
+    Json(db.inner().clone().find_game(id.as_str()).unwrap_or(None))
+}
+
+// Post request to create a new user, who is logged in straight away
 #[post("/new_user", format = "application/json", data = "<user>")]
-pub fn new_user(user: Json<User>) -> Json<String> {
-    match MongoDB::new() {
-        // Establish connection
-        Ok(mut db) => {
-            match db.add_user(&user.username, &user.password) {
-                // Add user to db
-                Ok(()) => {}
-                Err(_) => return Json(String::from("Username Taken")),
-            };
-        }
-        Err(_) => return Json(String::from("Database Connection Failed")),
+pub fn new_user(key: State<SessionKey>, db: State<MongoDB>, user: Json<User>) -> Json<SessionResponse> {
+    let mut db = db.inner().clone();
+    match db.add_user(&user.username, &user.password) {
+        // Add user to db
+        Ok(()) => {}
+        Err(_) => return Json(SessionResponse::failed("Username Taken")),
+    };
+    match db.create_session(&key, &user.username) {
+        Ok(token) => Json(SessionResponse { status: String::from("Created user"), token: Some(token) }),
+        Err(_) => Json(SessionResponse::failed("Login Failed")),
     }
-    Json(String::from(format!("Created user")))
 }
 
-// Post request to verify login request for existing user candidate:
+// Post request to verify login request for existing user candidate and start a session:
 #[post("/login", format = "application/json", data = "<user>")]
-pub fn authentication_verify(user: Json<User>) -> Json<String> {
-    match MongoDB::new() {
-        // Establish connection
-        Ok(mut db) => match db.authentication_verify(&user.username, &user.password) {
-            Ok(res) => {
-                if res == false {
-                    return Json(String::from("Login Failed"));
-                }
-            }
-            Err(_) => return Json(String::from("Login Failed")),
-        },
-        Err(_) => return Json(String::from("Database Connection Failed")),
+pub fn authentication_verify(key: State<SessionKey>, db: State<MongoDB>, user: Json<User>) -> Json<SessionResponse> {
+    let mut db = db.inner().clone();
+    match db.authentication_verify(&user.username, &user.password) {
+        Ok(true) => {}
+        _ => return Json(SessionResponse::failed("Login Failed")),
+    };
+    match db.create_session(&key, &user.username) {
+        Ok(token) => Json(SessionResponse { status: String::from("Login OK"), token: Some(token) }),
+        Err(_) => Json(SessionResponse::failed("Login Failed")),
+    }
+}
+
+// Post request to end the session the request was made with
+#[post("/logout")]
+pub fn logout(user: AuthUser, db: State<MongoDB>) -> Json<String> {
+    match db.inner().clone().revoke_session(&user.session_id) {
+        Ok(()) => Json(String::from("Logged out")),
+        Err(_) => Json(String::from("Logout Failed")),
     }
-    Json(String::from(format!("Login OK")))
 }
@@ -3,7 +3,13 @@ Database calls to update scores and get scores
 */
 use std::ptr::null;
 use crate::MongoDB;
+use crate::matchmaking::DEFAULT_RATING;
 use mongodb::bson::{doc, Document, from_document};
+use game_core::record::GameRecord;
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
 
 // Game stats struct
 #[derive(Debug, Serialize, Deserialize, FromForm)]
@@ -31,21 +37,31 @@ pub struct ChampStats {
     pub tie_count: i32,
 }
 
-// Update score struct
-#[derive(Debug, Serialize, Deserialize, FromForm)]
+// Update score struct: the whole game, which is replayed before it counts
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ScoreUpdate {
-    pub username: String,
-    pub mode: u8, // Mode 0 connect 4, 1 toot
-    pub result: u8,  // Result 0 lost, 1 win, 2 tie
-    pub human_flag: u8,
+    #[serde(flatten)]
+    pub game: GameRecord, // Mode 0 connect 4, 1 toot, the moves, the AI and its seed
+    pub result: u8,  // Claimed result 0 lost, 1 win, 2 tie
+    #[serde(default)]
+    pub hinted: u8, // 1 if the player asked for a hint during the game
+    #[serde(default)]
+    pub variant: String, // How it was played: local, computer, adaptive or perfect
+    #[serde(default)]
+    pub started_at: Option<i64>, // First move, in milliseconds since the Unix epoch
+    #[serde(default)]
+    pub client_version: String,
+    #[serde(default)]
+    pub game_id: Option<String>, // What the server started a game against the computer under
 }
 
 impl MongoDB {
 
     // DB function to update the score
-    pub fn update_score(&mut self, username: &String, mode: u8, result: u8, human_flag: u8) -> Result<bool, mongodb::error::Error> {
+    pub fn update_score(&mut self, username: &String, mode: u8, result: u8, hinted: u8) -> Result<bool, mongodb::error::Error> {
         let score_document = self.db.collection::<Document>("scores");
-        if human_flag == 1 {
+        // A win with hints is not a clean win, so it earns nothing; losses and ties still count
+        if hinted == 1 && result == 1 {
             return Ok(true);
         }
         let score = match mode {
@@ -79,6 +95,24 @@ impl MongoDB {
         Ok(true)
     }
 
+    // Online ratings of a user, Connect 4 first; users who never finished an online game have the default
+    pub fn ratings(&mut self, username: &String) -> Result<[i32; 2], mongodb::error::Error> {
+        let score_document = self.db.collection::<Document>("scores");
+        let res = score_document.find_one(doc! {"username": username}, None)?;
+        let rating = |field: &str| res.as_ref().and_then(|document| document.get_i32(field).ok()).unwrap_or(DEFAULT_RATING);
+        Ok([rating("c_rating"), rating("t_rating")])
+    }
+
+    // Store the ratings of both players after an online game of `mode`
+    pub fn set_ratings(&mut self, players: &[String; 2], mode: u8, ratings: [i32; 2]) -> Result<(), mongodb::error::Error> {
+        let score_document = self.db.collection::<Document>("scores");
+        let field = if mode == 0 { "c_rating" } else { "t_rating" };
+        for (player, rating) in players.iter().zip(ratings) {
+            score_document.update_one(doc! {"username": player}, doc! {"$set": {field: rating}}, None)?;
+        }
+        Ok(())
+    }
+
     // DB function to get the game scores and champion information:
     pub fn get_game_score(&mut self, username: String)
         -> Result<Option<(GameStats, ChampStats, ChampStats)>, mongodb::error::Error> {
@@ -183,11 +217,27 @@ pub struct User {
     pub password: String,
 }
 
+// Salted Argon2 hash of a password, in the PHC string format that records its own parameters
+fn hash_password(password: &str) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("Hashing with the default Argon2 parameters cannot fail")
+        .to_string()
+}
+
+fn verify_password(password: &str, password_hash: &str) -> bool {
+    match PasswordHash::new(password_hash) {
+        Ok(parsed) => Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok(),
+        Err(_) => false,
+    }
+}
+
 impl MongoDB {
     // Add a new user to the database:
     pub fn add_user(&mut self, username: &String, password: &String) -> Result<(), mongodb::error::Error> {
         let user_db = self.db.collection::<Document>("users");
-        let user = doc! {"username" : username, "password": password};
+        let user = doc! {"username" : username, "password_hash": hash_password(password)};
         user_db.insert_one(user, None)?;
         let score_document = self.db.collection("scores");
         let stats = doc! {
@@ -201,7 +251,9 @@ impl MongoDB {
 			"c_tie_count": 0,
 			"t_win_count": 0,
 			"t_lose_count": 0,
-			"t_tie_count": 0
+			"t_tie_count": 0,
+			"c_rating": DEFAULT_RATING,
+			"t_rating": DEFAULT_RATING
 		};
         score_document.insert_one(stats, None)?;
         Ok(())
@@ -210,11 +262,28 @@ impl MongoDB {
     // Check if user exists in the database and log in if does:
     pub fn authentication_verify(&mut self, username: &String, password: &String) -> Result<bool, mongodb::error::Error> {
         let user_db = self.db.collection::<Document>("users");
-        let user = doc! {"username" : username, "password": password};
-        let res = user_db.find_one(user, None)?;
-        return match res {
-            Some(_) => Ok(true),
-            None => Ok(false),
+        let res = user_db.find_one(doc! {"username" : username}, None)?;
+        let user = match res {
+            Some(user) => user,
+            None => return Ok(false),
+        };
+
+        if let Ok(password_hash) = user.get_str("password_hash") {
+            return Ok(verify_password(password, password_hash));
+        }
+
+        // Accounts created before hashing still hold the plaintext password:
+        // check it once more and replace it with a hash
+        match user.get_str("password") {
+            Ok(stored) if stored == password.as_str() => {
+                user_db.update_one(
+                    doc! {"username": username},
+                    doc! {"$set": {"password_hash": hash_password(password)}, "$unset": {"password": ""}},
+                    None,
+                )?;
+                Ok(true)
+            }
+            _ => Ok(false),
         }
     }
 }
\ No newline at end of file
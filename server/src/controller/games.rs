@@ -0,0 +1,288 @@
+/*
+Database calls to keep a record of every finished game in the games collection
+*/
+use crate::MongoDB;
+use crate::document_update::ScoreUpdate;
+use crate::live::FinishedGame;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use game_core::record::{Ai, Evaluator, StartedGame, CONNECT4, LOST, TOOT, WON};
+use mongodb::bson::{doc, from_bson, oid::ObjectId, to_bson, Bson, DateTime, Document};
+use mongodb::error::{ErrorKind, WriteFailure};
+use mongodb::options::FindOptions;
+use sha2::{Digest, Sha256};
+
+// Games on a page of the history when the request names no size, and the most it may ask for
+const PER_PAGE: u64 = 20;
+const MAX_PER_PAGE: u64 = 100;
+
+// How long a started game can wait for its result, in seconds (one day)
+const STARTED_GAME_LIFETIME: i64 = 24 * 60 * 60;
+
+// Request to start a game against the computer, sent before its first move
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameStart {
+    pub mode: u8, // Mode 0 connect 4, 1 toot
+    pub ai: Ai,
+    #[serde(default)]
+    pub evaluator: Evaluator,
+}
+
+// Query of the game history, every filter optional
+#[derive(Debug, FromForm)]
+pub struct HistoryQuery {
+    pub page: Option<u64>, // Counted from 1
+    pub per_page: Option<u64>,
+    pub game: Option<String>, // connect4 or toot
+    pub level: Option<u8>, // Computer level; adaptive games count under the level they started from
+    pub result: Option<String>, // won, lost or tie, from the player's side
+    pub from: Option<i64>, // Ended at or after, in milliseconds since the Unix epoch
+    pub to: Option<i64>, // Ended before
+}
+
+// One line of the game history
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub id: String,
+    pub game: String,
+    pub variant: String,
+    pub players: Vec<String>,
+    pub ai: Option<Ai>,
+    pub result: u8,
+    pub length: usize, // Moves played
+    pub started_at: i64,
+    pub ended_at: i64,
+}
+
+// One game with its moves, for the replay viewer
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoredGame {
+    #[serde(flatten)]
+    pub summary: GameSummary,
+    pub moves: Vec<String>,
+}
+
+// A page of the game history, newest first
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameHistory {
+    pub games: Vec<GameSummary>,
+    pub page: u64,
+    pub per_page: u64,
+    pub total: u64, // Games matching the filters over all pages
+}
+
+// Name of a game mode in the records
+fn game_name(mode: u8) -> &'static str {
+    match mode {
+        CONNECT4 => "connect4",
+        TOOT => "toot",
+        _ => "unknown",
+    }
+}
+
+// Result code of a result name in the history query
+fn result_code(result: &str) -> Option<i32> {
+    match result {
+        "lost" => Some(0),
+        "won" => Some(1),
+        "tie" => Some(2),
+        _ => None,
+    }
+}
+
+// A result code from the other player's side
+fn flipped(result: i32) -> i32 {
+    match result as u8 {
+        WON => LOST as i32,
+        LOST => WON as i32,
+        _ => result,
+    }
+}
+
+// Mongo filter of the games of `username` that match `query`
+fn history_filter(username: &String, query: &HistoryQuery) -> Document {
+    let mut filter = doc! {"players": username};
+    if let Some(game) = &query.game {
+        filter.insert("game", game);
+    }
+    if let Some(level) = query.level {
+        filter.insert("ai.level", doc! {"$gte": level as f64, "$lt": level as f64 + 1.0});
+    }
+    // Results are stored from the first player's side
+    if let Some(code) = query.result.as_ref().and_then(|result| result_code(result)) {
+        filter.insert("$or", vec![
+            doc! {"players.0": username, "result": code},
+            doc! {"players.1": username, "result": flipped(code)},
+        ]);
+    }
+    let mut ended_at = Document::new();
+    if let Some(from) = query.from {
+        ended_at.insert("$gte", DateTime::from_millis(from));
+    }
+    if let Some(to) = query.to {
+        ended_at.insert("$lt", DateTime::from_millis(to));
+    }
+    if !ended_at.is_empty() {
+        filter.insert("ended_at", ended_at);
+    }
+    filter
+}
+
+fn summary(document: &Document) -> Option<GameSummary> {
+    Some(GameSummary {
+        id: document.get_object_id("_id").ok()?.to_hex(),
+        game: document.get_str("game").ok()?.to_string(),
+        variant: document.get_str("variant").unwrap_or("").to_string(),
+        players: document.get_array("players").ok()?.iter().filter_map(|player| player.as_str().map(String::from)).collect(),
+        ai: document.get("ai").cloned().and_then(|ai| from_bson(ai).ok()).flatten(),
+        result: document.get_i32("result").ok()? as u8,
+        length: document.get_array("moves").map(|moves| moves.len()).unwrap_or(0),
+        started_at: document.get_datetime("started_at").ok()?.timestamp_millis(),
+        ended_at: document.get_datetime("ended_at").ok()?.timestamp_millis(),
+    })
+}
+
+// Code MongoDB fails an insert with when it breaks a unique index
+const DUPLICATE_KEY: i32 = 11000;
+
+// Hash of the player and their game, unique in `games` so a game can only be submitted once
+fn fingerprint(username: &String, game: &ScoreUpdate) -> String {
+    format!("{:x}", Sha256::digest(format!("{}:{}", username, game.game.key()).as_bytes()))
+}
+
+impl MongoDB {
+    // Start a game against the computer for a session. The server picks the seed the AI draws
+    // from, so a client cannot search for one under which a made up game replays
+    pub fn start_game(&mut self, username: &String, session_id: &String, game: &GameStart) -> Result<StartedGame, mongodb::error::Error> {
+        let started_games = self.db.collection::<Document>("started_games");
+        let id = ObjectId::new();
+        let seed = OsRng.next_u64();
+        // Stored as a date, so the TTL index on `expires` deletes a game nobody finished
+        let expires = DateTime::from_millis(DateTime::now().timestamp_millis() + STARTED_GAME_LIFETIME * 1000);
+        started_games.insert_one(doc! {
+            "_id": id,
+            "username": username,
+            "session_id": session_id,
+            "mode": game.mode as i32,
+            "ai": to_bson(&game.ai).unwrap_or(Bson::Null),
+            "evaluator": to_bson(&game.evaluator).unwrap_or(Bson::Null),
+            "seed": seed.to_string(),
+            "expires": expires,
+        }, None)?;
+        Ok(StartedGame { id: id.to_hex(), seed })
+    }
+
+    // Whether the server started `game` for this session, against the same computer with the
+    // same seed; the first result sent for a started game uses it up
+    pub fn take_started_game(&mut self, username: &String, session_id: &String, game: &ScoreUpdate) -> Result<bool, mongodb::error::Error> {
+        let started_games = self.db.collection::<Document>("started_games");
+        let id = match game.game_id.as_deref().map(ObjectId::parse_str) {
+            Some(Ok(id)) => id,
+            _ => return Ok(false),
+        };
+        let filter = doc! {
+            "_id": id,
+            "username": username,
+            "session_id": session_id,
+            "mode": game.game.mode as i32,
+            "ai": to_bson(&game.game.ai).unwrap_or(Bson::Null),
+            "evaluator": to_bson(&game.game.evaluator).unwrap_or(Bson::Null),
+            "seed": game.game.seed.to_string(),
+            "expires": {"$gt": DateTime::now()},
+        };
+        Ok(started_games.find_one_and_delete(filter, None)?.is_some())
+    }
+
+    // Store a finished game that passed its replay; false when the player already submitted it
+    pub fn add_game(&mut self, username: &String, game: &ScoreUpdate) -> Result<bool, mongodb::error::Error> {
+        let games = self.db.collection::<Document>("games");
+        let ended_at = DateTime::now();
+        // The client's clock decides when the game started, but never after it ended
+        let started_at = match game.started_at {
+            Some(millis) if millis > 0 && millis <= ended_at.timestamp_millis() => DateTime::from_millis(millis),
+            _ => ended_at,
+        };
+        let record = doc! {
+            "game": game_name(game.game.mode),
+            "variant": &game.variant,
+            "players": [username],
+            "ai": to_bson(&game.game.ai).unwrap_or(Bson::Null),
+            "evaluator": to_bson(&game.game.evaluator).unwrap_or(Bson::Null),
+            "seed": game.game.seed.to_string(),
+            "moves": game.game.moves.clone(),
+            "result": game.result as i32, // From the first player's side: 0 lost, 1 win, 2 tie
+            "hinted": game.hinted == 1,
+            "started_at": started_at,
+            "ended_at": ended_at,
+            "client_version": &game.client_version,
+            "fingerprint": fingerprint(username, game),
+        };
+        match games.insert_one(record, None) {
+            Ok(_) => Ok(true),
+            Err(error) => match *error.kind {
+                ErrorKind::Write(WriteFailure::WriteError(ref failure)) if failure.code == DUPLICATE_KEY => Ok(false),
+                _ => Err(error),
+            },
+        }
+    }
+
+    // Store a finished live game between two players
+    pub fn add_live_game(&mut self, game: &FinishedGame) -> Result<(), mongodb::error::Error> {
+        let games = self.db.collection::<Document>("games");
+        let record = doc! {
+            "_id": game.id,
+            "game": game_name(game.mode),
+            "variant": if game.rated { "online" } else { "private" },
+            "players": game.players.to_vec(),
+            "ai": Bson::Null,
+            "moves": game.moves.clone(),
+            "result": game.result as i32,
+            "ended_by": to_bson(&game.reason).unwrap_or(Bson::Null),
+            "hinted": false,
+            "started_at": game.started_at,
+            "ended_at": DateTime::now(),
+        };
+        games.insert_one(record, None)?;
+        Ok(())
+    }
+
+    // A page of the games `username` played, newest first, each result from their side
+    pub fn game_history(&mut self, username: &String, query: &HistoryQuery) -> Result<GameHistory, mongodb::error::Error> {
+        let games = self.db.collection::<Document>("games");
+        let page = query.page.unwrap_or(1).max(1);
+        let per_page = query.per_page.unwrap_or(PER_PAGE).clamp(1, MAX_PER_PAGE);
+        let filter = history_filter(username, query);
+
+        let total = games.count_documents(filter.clone(), None)?;
+        let options = FindOptions::builder()
+            .sort(doc! {"ended_at": -1})
+            .skip((page - 1).saturating_mul(per_page))
+            .limit(per_page as i64)
+            .build();
+        let mut found = Vec::new();
+        for document in games.find(filter, options)? {
+            if let Some(mut game) = summary(&document?) {
+                if game.players.first() != Some(username) {
+                    game.result = flipped(game.result as i32) as u8;
+                }
+                found.push(game);
+            }
+        }
+
+        Ok(GameHistory { games: found, page, per_page, total })
+    }
+
+    // The game stored under `id`, the hex form of its ObjectId
+    pub fn find_game(&mut self, id: &str) -> Result<Option<StoredGame>, mongodb::error::Error> {
+        let games = self.db.collection::<Document>("games");
+        let id = match ObjectId::parse_str(id) {
+            Ok(id) => id,
+            Err(_) => return Ok(None),
+        };
+
+        let res = games.find_one(doc! {"_id": id}, None)?;
+        Ok(res.and_then(|document| {
+            let moves = document.get_array("moves").ok()?.iter().filter_map(|mv| mv.as_str().map(String::from)).collect();
+            Some(StoredGame { summary: summary(&document)?, moves })
+        }))
+    }
+}
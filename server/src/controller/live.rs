@@ -0,0 +1,670 @@
+/*
+Live games between two logged in players over a WebSocket, which listens next to
+Rocket's HTTP port. The server keeps every game and plays each move on its own
+board before passing it on, so neither client can play an illegal move or out of turn.
+Players meet through the matchmaking queue, or in a private room opened with a code
+*/
+use crate::MongoDB;
+use crate::matchmaking::{rate, Matchmaker, Ticket, DEFAULT_RATING};
+use crate::rooms::Rooms;
+use crate::session::SessionKey;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use game_core::online::{loss_of, ClientMessage, EndReason, LiveState, Position, ServerMessage};
+use mongodb::bson::{oid::ObjectId, DateTime};
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tungstenite::{Message, WebSocket};
+
+// Where the WebSocket listens
+pub const LIVE_ADDRESS: &str = "0.0.0.0:8001";
+// How long a player has for each move before they lose on time
+const MOVE_TIME: Duration = Duration::from_secs(60);
+// How long the seat of a player who lost their connection is kept for them
+const RECONNECT_TIME: Duration = Duration::from_secs(30);
+// How long a connection waits for a message before passing on the hub's
+const POLL: Duration = Duration::from_millis(100);
+// How often the clocks are checked
+const TICK: Duration = Duration::from_secs(1);
+// How long a new socket has to finish the WebSocket handshake and then to log in
+const HELLO_TIME: Duration = Duration::from_secs(5);
+// Most sockets open at once; past it new ones are closed before they get a thread
+const MAX_CONNECTIONS: usize = 1000;
+
+// A finished game on its way to the games collection
+pub struct FinishedGame {
+    pub id: ObjectId,
+    pub mode: u8,
+    pub players: [String; 2],
+    pub moves: Vec<String>,
+    pub result: u8, // From the first player's side
+    pub reason: EndReason,
+    pub started_at: DateTime,
+    pub ratings: [i32; 2], // The players' new ratings
+    pub rated: bool, // False for a game in a private room
+}
+
+struct LiveGame {
+    id: ObjectId,
+    mode: u8,
+    players: [String; 2],
+    ratings: [i32; 2],
+    rated: bool,
+    position: Position,
+    moves: Vec<String>,
+    started_at: DateTime,
+    deadline: Instant, // When the player to move runs out of time
+    away: [Option<Instant>; 2], // When a player who lost their connection forfeits
+}
+
+impl LiveGame {
+    fn seat(&self, username: &String) -> Option<usize> {
+        self.players.iter().position(|player| player == username)
+    }
+
+    fn time_left(&self, deadline: Instant) -> u64 {
+        deadline.saturating_duration_since(Instant::now()).as_millis() as u64
+    }
+
+    fn state(&self, seat: usize) -> LiveState {
+        LiveState {
+            id: self.id.to_hex(),
+            mode: self.mode,
+            players: self.players.clone(),
+            ratings: self.ratings,
+            rated: self.rated,
+            seat,
+            moves: self.moves.clone(),
+            time_left: self.time_left(self.deadline),
+        }
+    }
+}
+
+// One open WebSocket; its thread sends on whatever arrives in `outbox`
+struct Connection {
+    username: Option<String>,
+    outbox: Sender<ServerMessage>,
+}
+
+// Everyone connected, waiting or playing. Connections talk to it only through
+// `ClientMessage`s in and `ServerMessage`s on their outbox
+pub struct Hub {
+    next_connection: u64,
+    connections: HashMap<u64, Connection>,
+    // The connection each logged in user plays through; a newer one replaces an older
+    online: HashMap<String, u64>,
+    matchmaker: Matchmaker,
+    // Everyone's rating for each game mode, read at login and kept up to date here
+    ratings: HashMap<String, [i32; 2]>,
+    rooms: Rooms,
+    games: HashMap<ObjectId, LiveGame>,
+    // The game each player is in
+    playing: HashMap<String, ObjectId>,
+    archive: Sender<FinishedGame>,
+}
+
+impl Hub {
+    pub fn new(archive: Sender<FinishedGame>) -> Hub {
+        Hub {
+            next_connection: 0,
+            connections: HashMap::new(),
+            online: HashMap::new(),
+            matchmaker: Matchmaker::default(),
+            ratings: HashMap::new(),
+            rooms: Rooms::default(),
+            games: HashMap::new(),
+            playing: HashMap::new(),
+            archive,
+        }
+    }
+
+    pub fn connect(&mut self, outbox: Sender<ServerMessage>) -> u64 {
+        self.next_connection += 1;
+        self.connections.insert(self.next_connection, Connection { username: None, outbox });
+        self.next_connection
+    }
+
+    // A connection logged in with the ratings the database has for the player;
+    // a player back in a game is sent where it stands
+    pub fn login(&mut self, connection: u64, username: String, ratings: [i32; 2]) {
+        // Ratings of games still on their way to the database are newer
+        self.ratings.entry(username.clone()).or_insert(ratings);
+        if let Some(older) = self.online.insert(username.clone(), connection) {
+            if older != connection {
+                self.send(older, ServerMessage::Error { message: "Connected from somewhere else".to_string() });
+                if let Some(older) = self.connections.get_mut(&older) {
+                    older.username = None;
+                }
+            }
+        }
+        if let Some(current) = self.connections.get_mut(&connection) {
+            current.username = Some(username.clone());
+        }
+        self.send(connection, ServerMessage::Welcome { username: username.clone() });
+
+        let game = match self.playing.get(&username).and_then(|id| self.games.get_mut(id)) {
+            Some(game) => game,
+            None => return,
+        };
+        let seat = game.seat(&username).unwrap_or(0);
+        let was_away = game.away[seat].take().is_some();
+        let state = game.state(seat);
+        let opponent = game.players[1 - seat].clone();
+        self.send(connection, ServerMessage::Game(state));
+        if was_away {
+            self.send_to(&opponent, ServerMessage::OpponentBack);
+        }
+    }
+
+    // A connection closed; its player stops waiting, and a game they are in waits for them a while.
+    // A room they opened stays open, but nobody can join it until they are back
+    pub fn disconnect(&mut self, connection: u64) {
+        let username = match self.connections.remove(&connection).and_then(|closed| closed.username) {
+            Some(username) => username,
+            None => return,
+        };
+        if self.online.get(&username) != Some(&connection) {
+            return;
+        }
+        self.online.remove(&username);
+        self.matchmaker.leave(&username);
+
+        let game = match self.playing.get(&username).and_then(|id| self.games.get_mut(id)) {
+            Some(game) => game,
+            None => return,
+        };
+        let seat = game.seat(&username).unwrap_or(0);
+        let forfeit = Instant::now() + RECONNECT_TIME;
+        game.away[seat] = Some(forfeit);
+        let time_left = game.time_left(forfeit);
+        let opponent = game.players[1 - seat].clone();
+        self.send_to(&opponent, ServerMessage::OpponentAway { time_left });
+    }
+
+    pub fn handle(&mut self, connection: u64, message: ClientMessage) {
+        let username = match self.connections.get(&connection).and_then(|current| current.username.clone()) {
+            Some(username) => username,
+            None => return self.send(connection, ServerMessage::Error { message: "Log in first".to_string() }),
+        };
+
+        match message {
+            ClientMessage::Hello { .. } => {}
+            ClientMessage::Seek { mode } => self.seek(connection, username, mode),
+            ClientMessage::Cancel => {
+                self.matchmaker.leave(&username);
+                self.rooms.close(&username);
+            }
+            ClientMessage::CreateRoom { mode } => self.open_room(connection, username, mode),
+            ClientMessage::JoinRoom { code } => self.join_room(connection, username, code),
+            ClientMessage::Move { mv } => self.play(connection, &username, mv),
+            ClientMessage::Resign => {
+                let id = match self.playing.get(&username) {
+                    Some(id) => *id,
+                    None => return,
+                };
+                if let Some(seat) = self.games.get(&id).and_then(|game| game.seat(&username)) {
+                    self.finish(id, loss_of(seat), EndReason::Resigned);
+                }
+            }
+        }
+    }
+
+    // Pairs whoever the widened ranges now allow, tells the rest how long they have waited,
+    // closes the rooms nobody joined, and ends the games whose player to move ran out of
+    // time or whose player did not come back
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        for room in self.rooms.expire(now) {
+            self.send_to(&room.host, ServerMessage::Error { message: "The room expired".to_string() });
+        }
+        self.match_players(now);
+        for username in self.matchmaker.waiting() {
+            if let Some(status) = self.matchmaker.status(&username, now) {
+                self.send_to(&username, ServerMessage::Queued(status));
+            }
+        }
+
+        let mut ended = Vec::new();
+        for (id, game) in self.games.iter() {
+            if now >= game.deadline {
+                ended.push((*id, loss_of(game.position.to_move()), EndReason::TimedOut));
+            // When both players are gone past their time, the one who left first loses
+            } else if let Some(seat) = (0..2).filter(|seat| game.away[*seat].is_some_and(|forfeit| now >= forfeit)).min_by_key(|seat| game.away[*seat]) {
+                ended.push((*id, loss_of(seat), EndReason::Abandoned));
+            }
+        }
+        for (id, result, reason) in ended {
+            self.finish(id, result, reason);
+        }
+    }
+
+    fn seek(&mut self, connection: u64, username: String, mode: u8) {
+        if self.playing.contains_key(&username) {
+            return self.send(connection, ServerMessage::Error { message: "Already in a game".to_string() });
+        }
+        if Position::new(mode).is_none() {
+            return self.send(connection, ServerMessage::Error { message: "No such game".to_string() });
+        }
+
+        let now = Instant::now();
+        self.rooms.close(&username);
+        let rating = self.rating(&username, mode);
+        self.matchmaker.join(Ticket { username: username.clone(), mode, rating, since: now });
+        self.match_players(now);
+        if let Some(status) = self.matchmaker.status(&username, now) {
+            self.send(connection, ServerMessage::Queued(status));
+        }
+    }
+
+    fn open_room(&mut self, connection: u64, username: String, mode: u8) {
+        if self.playing.contains_key(&username) {
+            return self.send(connection, ServerMessage::Error { message: "Already in a game".to_string() });
+        }
+        if Position::new(mode).is_none() {
+            return self.send(connection, ServerMessage::Error { message: "No such game".to_string() });
+        }
+
+        self.matchmaker.leave(&username);
+        let code = self.rooms.open(username, mode, Instant::now());
+        self.send(connection, ServerMessage::Room { code, mode });
+    }
+
+    // Seats a player in a room with its host; the host is told their room is still open
+    fn join_room(&mut self, connection: u64, username: String, code: String) {
+        if self.playing.contains_key(&username) {
+            return self.send(connection, ServerMessage::Error { message: "Already in a game".to_string() });
+        }
+        let (code, mode, host) = match self.rooms.get(&code) {
+            Ok(room) => (room.code.clone(), room.mode, room.host.clone()),
+            Err(message) => return self.send(connection, ServerMessage::Error { message }),
+        };
+        if host == username {
+            return self.send(connection, ServerMessage::Room { code, mode });
+        }
+        if !self.online.contains_key(&host) {
+            return self.send(connection, ServerMessage::Error { message: "The room's host is not connected".to_string() });
+        }
+
+        self.matchmaker.leave(&username);
+        self.rooms.close(&username);
+        if let Some(id) = self.start(mode, host, username, false) {
+            self.rooms.take(&code, id);
+        }
+    }
+
+    fn rating(&self, username: &String, mode: u8) -> i32 {
+        self.ratings.get(username).map_or(DEFAULT_RATING, |ratings| ratings[mode as usize])
+    }
+
+    fn match_players(&mut self, now: Instant) {
+        for (first, second) in self.matchmaker.pair(now) {
+            self.start(first.mode, first.username, second.username, true);
+        }
+    }
+
+    // Seats two players at a new game, in a random order
+    fn start(&mut self, mode: u8, first: String, second: String, rated: bool) -> Option<ObjectId> {
+        let position = Position::new(mode)?;
+        let players = if OsRng.next_u32().is_multiple_of(2) { [first, second] } else { [second, first] };
+        let ratings = [self.rating(&players[0], mode), self.rating(&players[1], mode)];
+        let game = LiveGame {
+            id: ObjectId::new(),
+            mode,
+            players,
+            ratings,
+            rated,
+            position,
+            moves: Vec::new(),
+            started_at: DateTime::now(),
+            deadline: Instant::now() + MOVE_TIME,
+            away: [None, None],
+        };
+
+        for seat in 0..2 {
+            self.playing.insert(game.players[seat].clone(), game.id);
+            self.send_to(&game.players[seat], ServerMessage::Game(game.state(seat)));
+        }
+        let id = game.id;
+        self.games.insert(id, game);
+        Some(id)
+    }
+
+    fn play(&mut self, connection: u64, username: &String, mv: String) {
+        let game = match self.playing.get(username).and_then(|id| self.games.get_mut(id)) {
+            Some(game) => game,
+            None => return self.send(connection, ServerMessage::Error { message: "Not in a game".to_string() }),
+        };
+        if game.seat(username) != Some(game.position.to_move()) {
+            return self.send(connection, ServerMessage::Error { message: "Not your move".to_string() });
+        }
+        if !game.position.play(&mv) {
+            return self.send(connection, ServerMessage::Error { message: "Illegal move".to_string() });
+        }
+
+        game.moves.push(mv.clone());
+        game.deadline = Instant::now() + MOVE_TIME;
+        let (id, players, result) = (game.id, game.players.clone(), game.position.result());
+        let time_left = game.time_left(game.deadline);
+        for player in players.iter() {
+            self.send_to(player, ServerMessage::Moved { mv: mv.clone(), time_left });
+        }
+        if let Some(result) = result {
+            self.finish(id, result, EndReason::Finished);
+        }
+    }
+
+    fn finish(&mut self, id: ObjectId, result: u8, reason: EndReason) {
+        let game = match self.games.remove(&id) {
+            Some(game) => game,
+            None => return,
+        };
+        let ratings = if game.rated { rate(game.ratings, result) } else { game.ratings };
+        self.rooms.finished(id);
+        for (seat, player) in game.players.iter().enumerate() {
+            self.playing.remove(player);
+            self.ratings.entry(player.clone()).or_insert([DEFAULT_RATING; 2])[game.mode as usize] = ratings[seat];
+            self.send_to(player, ServerMessage::Over { result, reason });
+        }
+        // The archive thread is gone only if the server is shutting down
+        let _ = self.archive.send(FinishedGame {
+            id: game.id,
+            mode: game.mode,
+            players: game.players,
+            moves: game.moves,
+            result,
+            reason,
+            started_at: game.started_at,
+            ratings,
+            rated: game.rated,
+        });
+    }
+
+    fn send(&self, connection: u64, message: ServerMessage) {
+        if let Some(current) = self.connections.get(&connection) {
+            // A closed connection's thread removes it from the hub on its way out
+            let _ = current.outbox.send(message);
+        }
+    }
+
+    // Sends to a player through their current connection, if they have one
+    fn send_to(&self, username: &String, message: ServerMessage) {
+        if let Some(connection) = self.online.get(username) {
+            self.send(*connection, message);
+        }
+    }
+}
+
+// User of a session token, if it is valid and not logged out, and their ratings
+fn authenticate(key: &SessionKey, db: &mut MongoDB, token: &str) -> Option<(String, [i32; 2])> {
+    let session_id = key.verify(token)?;
+    let username = db.session_user(&session_id).ok().flatten()?;
+    let ratings = db.ratings(&username).unwrap_or([DEFAULT_RATING; 2]);
+    Some((username, ratings))
+}
+
+// Runs one WebSocket until it closes: passes the hub's messages on and the client's to the hub.
+// A client that has not logged in within `HELLO_TIME` is sent away
+fn run_connection(stream: TcpStream, hub: Arc<Mutex<Hub>>, key: Arc<SessionKey>, mut db: MongoDB) {
+    let opened = Instant::now();
+    // A client stalling the handshake times out instead of holding its thread
+    if stream.set_read_timeout(Some(HELLO_TIME)).is_err() {
+        return;
+    }
+    let mut socket = match tungstenite::accept(stream) {
+        Ok(socket) => socket,
+        Err(_) => return,
+    };
+    // Reads give up after a while, so the outbox is never left waiting on a quiet client
+    if socket.get_mut().set_read_timeout(Some(POLL)).is_err() {
+        return;
+    }
+    let (outbox, inbox) = channel();
+    let errors = outbox.clone();
+    let connection = hub.lock().unwrap().connect(outbox);
+    let mut logged_in = false;
+
+    while pass_on(&mut socket, &inbox) {
+        if !logged_in && opened.elapsed() >= HELLO_TIME {
+            let _ = errors.send(ServerMessage::Error { message: "Log in first".to_string() });
+            pass_on(&mut socket, &inbox);
+            break;
+        }
+        let text = match socket.read_message() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => break,
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(error)) if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(_) => break,
+        };
+        match serde_json::from_str(&text) {
+            // Logging in needs the database, which is not worth holding the hub for
+            Ok(ClientMessage::Hello { token }) => match authenticate(&key, &mut db, &token) {
+                Some((username, ratings)) => {
+                    hub.lock().unwrap().login(connection, username, ratings);
+                    logged_in = true;
+                }
+                // A bad token closes the connection, so one socket cannot keep the database busy
+                None => {
+                    let _ = errors.send(ServerMessage::Error { message: "Session expired".to_string() });
+                    pass_on(&mut socket, &inbox);
+                    break;
+                }
+            },
+            Ok(message) => hub.lock().unwrap().handle(connection, message),
+            Err(_) => {
+                let _ = errors.send(ServerMessage::Error { message: "Unknown message".to_string() });
+            }
+        }
+    }
+    hub.lock().unwrap().disconnect(connection);
+}
+
+// Writes out everything waiting in the outbox; false once the socket is gone
+fn pass_on(socket: &mut WebSocket<TcpStream>, inbox: &Receiver<ServerMessage>) -> bool {
+    for message in inbox.try_iter() {
+        let text = serde_json::to_string(&message).unwrap_or_default();
+        if socket.write_message(Message::Text(text)).is_err() {
+            return false;
+        }
+    }
+    true
+}
+
+// Starts the WebSocket on its own threads: one per connection, up to `MAX_CONNECTIONS`, one for
+// the clocks and one that stores finished games. They all share the server's database client and its pool
+pub fn serve(key: SessionKey, db: MongoDB) {
+    let listener = match TcpListener::bind(LIVE_ADDRESS) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Live games are off, cannot listen on {}: {}", LIVE_ADDRESS, error);
+            return;
+        }
+    };
+    let (archive, finished) = channel::<FinishedGame>();
+    let hub = Arc::new(Mutex::new(Hub::new(archive)));
+    let key = Arc::new(key);
+
+    let mut archive_db = db.clone();
+    thread::spawn(move || {
+        for game in finished.iter() {
+            let stored = archive_db.add_live_game(&game).and_then(|_| {
+                if !game.rated {
+                    return Ok(());
+                }
+                archive_db.set_ratings(&game.players, game.mode, game.ratings)
+            });
+            if let Err(error) = stored {
+                eprintln!("Could not store live game {}: {}", game.id, error);
+            }
+        }
+    });
+
+    let clock = Arc::clone(&hub);
+    thread::spawn(move || loop {
+        thread::sleep(TICK);
+        clock.lock().unwrap().tick();
+    });
+
+    let open = Arc::new(AtomicUsize::new(0));
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // Dropping the stream closes it
+            if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                open.fetch_sub(1, Ordering::SeqCst);
+                continue;
+            }
+            let (hub, key, db, open) = (Arc::clone(&hub), Arc::clone(&key), db.clone(), Arc::clone(&open));
+            thread::spawn(move || {
+                run_connection(stream, hub, key, db);
+                open.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rooms::ROOM_TIME;
+    use game_core::record::LOST;
+
+    // A hub with two logged in players, their outboxes and the archive of finished games
+    fn hub_with_players() -> (Hub, [Receiver<ServerMessage>; 2], Receiver<FinishedGame>) {
+        let (archive, finished) = channel();
+        let mut hub = Hub::new(archive);
+        let inboxes = ["alice", "bob"].map(|username| {
+            let (outbox, inbox) = channel();
+            let connection = hub.connect(outbox);
+            hub.login(connection, username.to_string(), [DEFAULT_RATING; 2]);
+            inbox
+        });
+        (hub, inboxes, finished)
+    }
+
+    fn drain(inbox: &Receiver<ServerMessage>) -> Vec<ServerMessage> {
+        inbox.try_iter().collect()
+    }
+
+    // Seats both players at a game through the queue and returns its id
+    fn start_game(hub: &mut Hub, inboxes: &[Receiver<ServerMessage>; 2]) -> ObjectId {
+        hub.handle(1, ClientMessage::Seek { mode: 0 });
+        hub.handle(2, ClientMessage::Seek { mode: 0 });
+        inboxes.iter().for_each(|inbox| { drain(inbox); });
+        hub.playing["alice"]
+    }
+
+    #[test]
+    fn tick_pairs_players_once_their_ranges_meet() {
+        let (mut hub, inboxes, _) = hub_with_players();
+        hub.ratings.insert("bob".to_string(), [DEFAULT_RATING + 300; 2]);
+        hub.handle(1, ClientMessage::Seek { mode: 0 });
+        hub.handle(2, ClientMessage::Seek { mode: 0 });
+
+        hub.tick();
+        assert!(hub.games.is_empty());
+        assert!(drain(&inboxes[0]).iter().any(|message| matches!(message, ServerMessage::Queued(_))));
+
+        // Pretend both have waited long enough for 300 points
+        hub.matchmaker = Matchmaker::default();
+        let since = Instant::now() - Duration::from_secs(30);
+        for (username, rating) in [("alice", DEFAULT_RATING), ("bob", DEFAULT_RATING + 300)] {
+            hub.matchmaker.join(Ticket { username: username.to_string(), mode: 0, rating, since });
+        }
+        hub.tick();
+        assert_eq!(hub.games.len(), 1);
+        assert!(drain(&inboxes[1]).iter().any(|message| matches!(message, ServerMessage::Game(state) if state.rated)));
+    }
+
+    #[test]
+    fn tick_ends_a_game_on_time() {
+        let (mut hub, inboxes, finished) = hub_with_players();
+        let id = start_game(&mut hub, &inboxes);
+        hub.games.get_mut(&id).unwrap().deadline = Instant::now() - Duration::from_secs(1);
+        let first = hub.games[&id].players[0].clone();
+
+        hub.tick();
+        let game = finished.try_recv().unwrap();
+        assert_eq!((game.id, game.reason, game.result), (id, EndReason::TimedOut, LOST));
+        assert_eq!(game.ratings[0], DEFAULT_RATING - 16);
+        assert!(hub.playing.is_empty());
+        assert!(hub.ratings[&first][0] < DEFAULT_RATING);
+        assert!(drain(&inboxes[0]).contains(&ServerMessage::Over { result: LOST, reason: EndReason::TimedOut }));
+    }
+
+    #[test]
+    fn tick_ends_a_game_its_player_left() {
+        let (mut hub, inboxes, finished) = hub_with_players();
+        let id = start_game(&mut hub, &inboxes);
+        let seat = hub.games[&id].seat(&"bob".to_string()).unwrap();
+        hub.disconnect(2);
+        assert!(drain(&inboxes[0]).iter().any(|message| matches!(message, ServerMessage::OpponentAway { .. })));
+
+        hub.tick();
+        assert!(finished.try_recv().is_err());
+        hub.games.get_mut(&id).unwrap().away[seat] = Some(Instant::now() - Duration::from_secs(1));
+        hub.tick();
+        let game = finished.try_recv().unwrap();
+        assert_eq!((game.reason, game.result), (EndReason::Abandoned, loss_of(seat)));
+    }
+
+    #[test]
+    fn tick_forfeits_the_player_who_left_first_when_both_are_gone() {
+        let (mut hub, inboxes, finished) = hub_with_players();
+        let id = start_game(&mut hub, &inboxes);
+        hub.disconnect(1);
+        hub.disconnect(2);
+
+        // Seat 1 left first, so it is further past its time than seat 0
+        let now = Instant::now();
+        hub.games.get_mut(&id).unwrap().away = [Some(now - Duration::from_secs(1)), Some(now - Duration::from_secs(5))];
+        hub.tick();
+        let game = finished.try_recv().unwrap();
+        assert_eq!((game.reason, game.result), (EndReason::Abandoned, loss_of(1)));
+        assert!(hub.games.is_empty());
+    }
+
+    #[test]
+    fn tick_closes_rooms_nobody_joined() {
+        let (mut hub, inboxes, _) = hub_with_players();
+        let code = hub.rooms.open("alice".to_string(), 0, Instant::now() - ROOM_TIME);
+
+        hub.tick();
+        assert!(hub.rooms.get(&code).is_err());
+        assert!(drain(&inboxes[0]).contains(&ServerMessage::Error { message: "The room expired".to_string() }));
+    }
+
+    #[test]
+    fn a_room_game_is_unrated_and_frees_its_code() {
+        let (mut hub, inboxes, finished) = hub_with_players();
+        hub.handle(1, ClientMessage::CreateRoom { mode: 1 });
+        let code = match drain(&inboxes[0]).pop() {
+            Some(ServerMessage::Room { code, .. }) => code,
+            other => panic!("no room opened: {:?}", other),
+        };
+        hub.handle(2, ClientMessage::JoinRoom { code: code.clone() });
+        assert_eq!(hub.rooms.get(&code).err(), Some("This room is full".to_string()));
+
+        hub.handle(1, ClientMessage::Resign);
+        let game = finished.try_recv().unwrap();
+        assert!(!game.rated);
+        assert_eq!(game.ratings, [DEFAULT_RATING; 2]);
+        assert_eq!(hub.rooms.get(&code).err(), Some("No such room, or it expired".to_string()));
+    }
+
+    #[test]
+    fn resigning_outside_a_game_does_nothing() {
+        let (mut hub, inboxes, finished) = hub_with_players();
+        hub.handle(1, ClientMessage::Resign);
+
+        // A game already gone from the hub while the player is still marked as in it
+        hub.playing.insert("alice".to_string(), ObjectId::new());
+        hub.handle(1, ClientMessage::Resign);
+        assert!(finished.try_recv().is_err());
+        assert!(drain(&inboxes[0]).iter().all(|message| !matches!(message, ServerMessage::Over { .. })));
+    }
+}
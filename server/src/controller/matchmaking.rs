@@ -0,0 +1,193 @@
+/*
+Matchmaking for live games: players wait in a queue per game and are paired with
+someone close to their rating, a range that widens the longer they wait. Online
+results move the ratings, Elo style
+*/
+use game_core::online::QueueStatus;
+use game_core::record::{LOST, WON};
+use std::time::Instant;
+
+// Rating of a player who has not finished an online game of that kind yet
+pub const DEFAULT_RATING: i32 = 1200;
+// Rating distance accepted on joining the queue
+const INITIAL_RANGE: i32 = 100;
+// How much the range widens every second of waiting
+const WIDEN_PER_SECOND: i32 = 10;
+// Furthest apart two players can be paired
+const MAX_RANGE: i32 = 800;
+// Most a single game moves a rating
+const K_FACTOR: f64 = 32.0;
+
+// A player waiting in the queue
+pub struct Ticket {
+    pub username: String,
+    pub mode: u8,
+    pub rating: i32,
+    pub since: Instant,
+}
+
+impl Ticket {
+    // Rating distance the player accepts after waiting until `now`
+    pub fn range(&self, now: Instant) -> i32 {
+        let waited = now.saturating_duration_since(self.since).as_secs() as i32;
+        (INITIAL_RANGE + waited * WIDEN_PER_SECOND).min(MAX_RANGE)
+    }
+
+    // Both players accept each other
+    fn matches(&self, other: &Ticket, now: Instant) -> bool {
+        let distance = (self.rating - other.rating).abs();
+        self.mode == other.mode && self.username != other.username
+            && distance <= self.range(now) && distance <= other.range(now)
+    }
+}
+
+// The queue, longest waiting first. It knows nothing of connections, so the hub
+// decides what to tell the players it pairs
+#[derive(Default)]
+pub struct Matchmaker {
+    queue: Vec<Ticket>,
+}
+
+impl Matchmaker {
+    // Puts a player in the queue, in place of any ticket they already had
+    pub fn join(&mut self, ticket: Ticket) {
+        self.leave(&ticket.username);
+        self.queue.push(ticket);
+    }
+
+    // Takes a player out of the queue; false if they were not in it
+    pub fn leave(&mut self, username: &String) -> bool {
+        let before = self.queue.len();
+        self.queue.retain(|ticket| &ticket.username != username);
+        self.queue.len() != before
+    }
+
+    // Takes every pair that can play out of the queue. The longest waiting player
+    // is served first, with the closest rating among those who accept them
+    pub fn pair(&mut self, now: Instant) -> Vec<(Ticket, Ticket)> {
+        let mut pairs = Vec::new();
+        let mut first = 0;
+        while first < self.queue.len() {
+            let closest = (first + 1..self.queue.len())
+                .filter(|other| self.queue[first].matches(&self.queue[*other], now))
+                .min_by_key(|other| (self.queue[first].rating - self.queue[*other].rating).abs());
+            match closest {
+                Some(other) => {
+                    let second = self.queue.remove(other);
+                    pairs.push((self.queue.remove(first), second));
+                }
+                None => first += 1,
+            }
+        }
+        pairs
+    }
+
+    // Where a player stands in the queue, `None` if they are not in it
+    pub fn status(&self, username: &String, now: Instant) -> Option<QueueStatus> {
+        let ticket = self.queue.iter().find(|ticket| &ticket.username == username)?;
+        Some(QueueStatus {
+            mode: ticket.mode,
+            rating: ticket.rating,
+            range: ticket.range(now),
+            waiting: now.saturating_duration_since(ticket.since).as_millis() as u64,
+            queued: self.queue.iter().filter(|other| other.mode == ticket.mode).count(),
+        })
+    }
+
+    // Everyone in the queue
+    pub fn waiting(&self) -> Vec<String> {
+        self.queue.iter().map(|ticket| ticket.username.clone()).collect()
+    }
+}
+
+// Both ratings after a game; `result` is from the first player's side
+pub fn rate(ratings: [i32; 2], result: u8) -> [i32; 2] {
+    let score = match result {
+        WON => 1.0,
+        LOST => 0.0,
+        _ => 0.5,
+    };
+    let expected = 1.0 / (1.0 + 10f64.powf((ratings[1] - ratings[0]) as f64 / 400.0));
+    let change = (K_FACTOR * (score - expected)).round() as i32;
+    [ratings[0] + change, ratings[1] - change]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_core::record::TIE;
+    use std::time::Duration;
+
+    fn ticket(username: &str, mode: u8, rating: i32, since: Instant) -> Ticket {
+        Ticket { username: username.to_string(), mode, rating, since }
+    }
+
+    fn names(pairs: &[(Ticket, Ticket)]) -> Vec<(&str, &str)> {
+        pairs.iter().map(|(first, second)| (first.username.as_str(), second.username.as_str())).collect()
+    }
+
+    #[test]
+    fn serves_the_longest_waiting_with_the_closest_rating() {
+        let now = Instant::now();
+        let mut matchmaker = Matchmaker::default();
+        matchmaker.join(ticket("first", 0, 1200, now));
+        matchmaker.join(ticket("far", 0, 1290, now));
+        matchmaker.join(ticket("near", 0, 1210, now));
+        matchmaker.join(ticket("other game", 1, 1200, now));
+
+        let pairs = matchmaker.pair(now);
+        assert_eq!(names(&pairs), vec![("first", "near")]);
+        assert_eq!(matchmaker.waiting(), vec!["far".to_string(), "other game".to_string()]);
+    }
+
+    #[test]
+    fn ranges_widen_while_waiting_up_to_the_cap() {
+        let since = Instant::now();
+        let waiting = ticket("a", 0, 1200, since);
+
+        assert_eq!(waiting.range(since), INITIAL_RANGE);
+        assert_eq!(waiting.range(since + Duration::from_secs(5)), INITIAL_RANGE + 5 * WIDEN_PER_SECOND);
+        assert_eq!(waiting.range(since + Duration::from_secs(3600)), MAX_RANGE);
+    }
+
+    #[test]
+    fn pairs_distant_ratings_once_both_ranges_reach() {
+        let since = Instant::now();
+        let mut matchmaker = Matchmaker::default();
+        matchmaker.join(ticket("low", 0, 1000, since));
+        matchmaker.join(ticket("high", 0, 1300, since));
+
+        assert!(matchmaker.pair(since + Duration::from_secs(19)).is_empty());
+        assert_eq!(names(&matchmaker.pair(since + Duration::from_secs(20))), vec![("low", "high")]);
+    }
+
+    #[test]
+    fn rejoining_replaces_the_old_ticket() {
+        let now = Instant::now();
+        let mut matchmaker = Matchmaker::default();
+        matchmaker.join(ticket("a", 0, 1200, now));
+        matchmaker.join(ticket("a", 1, 1200, now));
+
+        assert_eq!(matchmaker.waiting(), vec!["a".to_string()]);
+        assert_eq!(matchmaker.status(&"a".to_string(), now).map(|status| status.mode), Some(1));
+        assert!(matchmaker.leave(&"a".to_string()));
+        assert!(!matchmaker.leave(&"a".to_string()));
+    }
+
+    #[test]
+    fn ratings_move_by_the_same_amount_both_ways() {
+        assert_eq!(rate([1200, 1200], WON), [1216, 1184]);
+        assert_eq!(rate([1200, 1200], TIE), [1200, 1200]);
+
+        for (ratings, result) in [([1500, 1100], WON), ([1500, 1100], LOST), ([1100, 1500], TIE)] {
+            let after = rate(ratings, result);
+            assert_eq!(after[0] + after[1], ratings[0] + ratings[1]);
+
+            // The same game seen from the other side
+            let mirrored = rate([ratings[1], ratings[0]], match result { WON => LOST, LOST => WON, tie => tie });
+            assert_eq!(mirrored, [after[1], after[0]]);
+        }
+        // The favourite gains little from a win and loses much from a loss
+        assert!(rate([1500, 1100], WON)[0] - 1500 < 1500 - rate([1500, 1100], LOST)[0]);
+    }
+}
@@ -0,0 +1,143 @@
+/*
+Private rooms for live games: a player opens one for a game and shares its short code,
+and whoever comes with the code takes the second seat. Unused rooms expire, and a room
+stays taken until its game is over so nobody else can sit down in it
+*/
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use mongodb::bson::oid::ObjectId;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// How long a room waits for its second player
+pub const ROOM_TIME: Duration = Duration::from_secs(10 * 60);
+// Characters of a code, without those easily read as one another (0/O, 1/I)
+const CODE_CHARACTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 6;
+
+// A room waiting for its second player
+pub struct Room {
+    pub code: String,
+    pub mode: u8,
+    pub host: String,
+    pub opened: Instant,
+}
+
+// The open rooms by code, and the game each taken room's code now belongs to
+#[derive(Default)]
+pub struct Rooms {
+    open: HashMap<String, Room>,
+    taken: HashMap<String, ObjectId>,
+}
+
+impl Rooms {
+    // Opens a room for `host`, closing any other they had open, and returns its code
+    pub fn open(&mut self, host: String, mode: u8, now: Instant) -> String {
+        self.close(&host);
+        let code = loop {
+            let code: String = (0..CODE_LENGTH)
+                .map(|_| CODE_CHARACTERS[OsRng.next_u32() as usize % CODE_CHARACTERS.len()] as char)
+                .collect();
+            if !self.open.contains_key(&code) && !self.taken.contains_key(&code) {
+                break code;
+            }
+        };
+        self.open.insert(code.clone(), Room { code: code.clone(), mode, host, opened: now });
+        code
+    }
+
+    // The open room with this code, or why there is no seat in it; codes are read
+    // without regard to case
+    pub fn get(&self, code: &str) -> Result<&Room, String> {
+        let code = code.trim().to_uppercase();
+        match self.open.get(&code) {
+            Some(room) => Ok(room),
+            None if self.taken.contains_key(&code) => Err("This room is full".to_string()),
+            None => Err("No such room, or it expired".to_string()),
+        }
+    }
+
+    // Takes a room out of the open ones for the game its two players now play
+    pub fn take(&mut self, code: &str, game: ObjectId) -> Option<Room> {
+        let room = self.open.remove(code)?;
+        self.taken.insert(room.code.clone(), game);
+        Some(room)
+    }
+
+    // Closes the room `host` has open; false if they had none
+    pub fn close(&mut self, host: &String) -> bool {
+        let before = self.open.len();
+        self.open.retain(|_, room| &room.host != host);
+        self.open.len() != before
+    }
+
+    // Frees the code of a room whose game is over
+    pub fn finished(&mut self, game: ObjectId) {
+        self.taken.retain(|_, id| *id != game);
+    }
+
+    // Takes out the rooms nobody joined in time
+    pub fn expire(&mut self, now: Instant) -> Vec<Room> {
+        let expired: Vec<String> = self.open.values()
+            .filter(|room| now.saturating_duration_since(room.opened) >= ROOM_TIME)
+            .map(|room| room.code.clone())
+            .collect();
+        expired.iter().filter_map(|code| self.open.remove(code)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_rooms_under_readable_codes() {
+        let mut rooms = Rooms::default();
+        let code = rooms.open("host".to_string(), 1, Instant::now());
+
+        assert_eq!(code.len(), CODE_LENGTH);
+        assert!(code.bytes().all(|character| CODE_CHARACTERS.contains(&character)));
+        let room = rooms.get(&format!(" {} ", code.to_lowercase())).unwrap();
+        assert_eq!((room.host.as_str(), room.mode), ("host", 1));
+    }
+
+    #[test]
+    fn a_host_keeps_one_room() {
+        let mut rooms = Rooms::default();
+        let first = rooms.open("host".to_string(), 0, Instant::now());
+        let second = rooms.open("host".to_string(), 1, Instant::now());
+
+        assert!(rooms.get(&first).is_err());
+        assert!(rooms.get(&second).is_ok());
+        assert!(rooms.close(&"host".to_string()));
+        assert!(!rooms.close(&"host".to_string()));
+        assert!(rooms.get(&second).is_err());
+    }
+
+    #[test]
+    fn a_taken_room_is_full_until_its_game_finishes() {
+        let mut rooms = Rooms::default();
+        let code = rooms.open("host".to_string(), 0, Instant::now());
+        let game = ObjectId::new();
+
+        assert_eq!(rooms.take(&code, game).map(|room| room.host), Some("host".to_string()));
+        assert!(rooms.take(&code, game).is_none());
+        assert_eq!(rooms.get(&code).err(), Some("This room is full".to_string()));
+
+        rooms.finished(ObjectId::new());
+        assert_eq!(rooms.get(&code).err(), Some("This room is full".to_string()));
+        rooms.finished(game);
+        assert_eq!(rooms.get(&code).err(), Some("No such room, or it expired".to_string()));
+    }
+
+    #[test]
+    fn rooms_expire_once_nobody_joined_in_time() {
+        let opened = Instant::now();
+        let mut rooms = Rooms::default();
+        let code = rooms.open("host".to_string(), 0, opened);
+
+        assert!(rooms.expire(opened + ROOM_TIME - Duration::from_secs(1)).is_empty());
+        let expired = rooms.expire(opened + ROOM_TIME);
+        assert_eq!(expired.iter().map(|room| room.code.clone()).collect::<Vec<_>>(), vec![code.clone()]);
+        assert!(rooms.get(&code).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+/*
+Session tokens: issued on login, signed with the server's key and checked by the
+`AuthUser` request guard, which resolves the user behind a request
+*/
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::MongoDB;
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use hmac::{Hmac, Mac};
+use mongodb::bson::{doc, DateTime, Document};
+use rocket::http::Status;
+use rocket::request::{self, FromRequest, Request};
+use rocket::{Outcome, State};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// How long a session lasts after login, in seconds (one week)
+pub const SESSION_LIFETIME: i64 = 7 * 24 * 60 * 60;
+
+// Key the tokens are signed with. Set SESSION_SECRET to keep sessions valid across
+// restarts; without it a random key is made and every restart logs everyone out
+#[derive(Clone)]
+pub struct SessionKey(Vec<u8>);
+
+impl SessionKey {
+    pub fn from_env() -> SessionKey {
+        match std::env::var("SESSION_SECRET") {
+            Ok(secret) if !secret.is_empty() => SessionKey(secret.into_bytes()),
+            _ => {
+                let mut key = vec![0u8; 32];
+                OsRng.fill_bytes(&mut key);
+                SessionKey(key)
+            }
+        }
+    }
+
+    fn mac(&self, payload: &str) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.0).expect("HMAC takes keys of any length");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    // Token of the form "<session id>.<expiry>.<signature>"
+    pub fn sign(&self, session_id: &str, expires: i64) -> String {
+        let payload = format!("{}.{}", session_id, expires);
+        let signature = to_hex(&self.mac(&payload).finalize().into_bytes());
+        format!("{}.{}", payload, signature)
+    }
+
+    // Session id of a token this key signed and that has not expired yet
+    pub fn verify(&self, token: &str) -> Option<String> {
+        let mut parts = token.splitn(3, '.');
+        let (session_id, expires, signature) = (parts.next()?, parts.next()?, parts.next()?);
+        let expires: i64 = expires.parse().ok()?;
+
+        let payload = format!("{}.{}", session_id, expires);
+        self.mac(&payload).verify_slice(&from_hex(signature)?).ok()?;
+        if expires <= now() {
+            return None;
+        }
+        Some(session_id.to_string())
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_secs() as i64).unwrap_or(0)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect()
+}
+
+impl MongoDB {
+    // Start a session for a user who just logged in and return its signed token
+    pub fn create_session(&mut self, key: &SessionKey, username: &String) -> Result<String, mongodb::error::Error> {
+        let mut id = [0u8; 16];
+        OsRng.fill_bytes(&mut id);
+        let session_id = to_hex(&id);
+        let expires = now() + SESSION_LIFETIME;
+
+        // Stored as a date, so the TTL index on `expires` deletes the session once it is over
+        let sessions = self.db.collection::<Document>("sessions");
+        sessions.insert_one(doc! {"session_id": &session_id, "username": username, "expires": DateTime::from_millis(expires * 1000)}, None)?;
+        Ok(key.sign(&session_id, expires))
+    }
+
+    // User of a live session; revoked sessions are gone from the collection
+    pub fn session_user(&mut self, session_id: &String) -> Result<Option<String>, mongodb::error::Error> {
+        let sessions = self.db.collection::<Document>("sessions");
+        // MongoDB removes expired sessions only about once a minute, so check the time as well
+        let res = sessions.find_one(doc! {"session_id": session_id, "expires": {"$gt": DateTime::now()}}, None)?;
+        Ok(res.and_then(|session| session.get_str("username").ok().map(String::from)))
+    }
+
+    // Log a session out, so its token stops working before it expires
+    pub fn revoke_session(&mut self, session_id: &String) -> Result<(), mongodb::error::Error> {
+        let sessions = self.db.collection::<Document>("sessions");
+        sessions.delete_one(doc! {"session_id": session_id}, None)?;
+        Ok(())
+    }
+}
+
+// The logged in user making a request, from its "Authorization: Bearer <token>" header.
+// Requests without a valid, unrevoked token fail with 401 Unauthorized
+pub struct AuthUser {
+    pub username: String,
+    pub session_id: String,
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for AuthUser {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<AuthUser, ()> {
+        let key = match request.guard::<State<SessionKey>>() {
+            Outcome::Success(key) => key,
+            _ => return Outcome::Failure((Status::InternalServerError, ())),
+        };
+        let token = match request.headers().get_one("Authorization") {
+            Some(header) if header.starts_with("Bearer ") => &header["Bearer ".len()..],
+            _ => return Outcome::Failure((Status::Unauthorized, ())),
+        };
+        let session_id = match key.verify(token) {
+            Some(session_id) => session_id,
+            None => return Outcome::Failure((Status::Unauthorized, ())),
+        };
+
+        let db = match request.guard::<State<MongoDB>>() {
+            Outcome::Success(db) => db,
+            _ => return Outcome::Failure((Status::InternalServerError, ())),
+        };
+
+        match db.inner().clone().session_user(&session_id) {
+            Ok(Some(username)) => Outcome::Success(AuthUser { username, session_id }),
+            Ok(None) => Outcome::Failure((Status::Unauthorized, ())),
+            Err(_) => Outcome::Failure((Status::ServiceUnavailable, ())),
+        }
+    }
+}
+
+// Reply to /login and /new_user: a status message and, when it worked, the session token
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionResponse {
+    pub status: String,
+    pub token: Option<String>,
+}
+
+impl SessionResponse {
+    pub fn failed(status: &str) -> SessionResponse {
+        SessionResponse { status: status.to_string(), token: None }
+    }
+}
@@ -12,7 +12,19 @@ use rocket::ignite;
 pub mod db_request;
 #[path="controller/document_update.rs"]
 pub mod document_update;
+#[path="controller/games.rs"]
+pub mod games;
+#[path="controller/live.rs"]
+pub mod live;
+#[path="controller/matchmaking.rs"]
+pub mod matchmaking;
+#[path="controller/rooms.rs"]
+pub mod rooms;
+#[path="controller/session.rs"]
+pub mod session;
 
+// Cloning shares the client and its connection pool
+#[derive(Clone)]
 pub struct MongoDB {
     db: Database,
 }
@@ -35,7 +47,11 @@ fn get_all_route() -> Vec<rocket::Route> {
         db_request::new_user,
         db_request::authentication_verify,
         db_request::get_scores,
+        db_request::start_game,
         db_request::update_score,
+        db_request::logout,
+        db_request::game_history,
+        db_request::game_record,
     ]
 }
 
@@ -46,6 +62,17 @@ fn main() {
         .allowed_methods(vec![Get, Post].into_iter().map(From::from).collect())
         .allow_credentials(true);
     let routes = get_all_route();
+    // One database client for the whole server; every route, the session guard and
+    // the live games clone it, which shares its connection pool
+    let db = MongoDB::new().expect("Invalid MongoDB connection options");
+    // Live games check the same session tokens as the routes
+    let key = session::SessionKey::from_env();
+    live::serve(key.clone(), db.clone());
     // Ignite the rocket:
-    ignite().attach(cors_options.to_cors().unwrap()).mount("/", routes).launch();
+    ignite()
+        .attach(cors_options.to_cors().unwrap())
+        .manage(key)
+        .manage(db)
+        .mount("/", routes)
+        .launch();
 }